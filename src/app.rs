@@ -1,5 +1,8 @@
 use crate::app_discovery::DiscoveredApp;
-use crate::config::{Action, AnchorKey, Binding, Browser, Config, UrlMatchType};
+use crate::config::{
+    expand_home, Action, ActionMode, AnchorKey, AppleScript, Binding, Browser, Config,
+    KeyModifier, UrlMatchOptions, UrlMatchType,
+};
 use std::collections::{HashMap, HashSet};
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -19,7 +22,11 @@ pub enum Tab {
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum EditorField {
     Key,
+    Sequence,
     Description,
+    OnlyIn,
+    ExceptIn,
+    CycleReset,
     Actions,
 }
 
@@ -28,7 +35,14 @@ pub enum ActionEditorField {
     Type,
     Target,
     MatchType,
+    MatchOptions,
     Browser,
+    Profile,
+    Modifiers,
+    Input,
+    ScriptSource,
+    WithApp,
+    Toggle,
 }
 
 #[derive(Debug, Clone)]
@@ -42,8 +56,17 @@ pub struct ActionEditor {
     pub action_type: ActionType,
     pub target: String,
     pub bundle_id: Option<String>, // For App actions
+    pub toggle: bool,              // For App actions: hide when already frontmost
     pub match_type: UrlMatchType,
+    pub url_options: UrlMatchOptions,
+    pub url_option_cursor: usize,
     pub browser: Option<Browser>,
+    pub profile: Option<String>,
+    pub modifiers: Vec<KeyModifier>, // For Keystroke actions
+    pub modifier_cursor: usize,
+    pub input: String,          // For Shortcut actions (empty = no input)
+    pub script_from_file: bool, // For AppleScript actions: target is a path, not the script
+    pub with_app: String,       // For Open actions: bundle ID (empty = default app)
     pub field: ActionEditorField,
     pub edit_mode: bool, // True when actively editing Target field, false when navigating
 }
@@ -53,6 +76,12 @@ pub enum ActionType {
     App,
     Url,
     Shell,
+    Open,
+    Shortcut,
+    AppleScript,
+    Delay,
+    Text,
+    Keystroke,
 }
 
 impl ActionType {
@@ -61,6 +90,12 @@ impl ActionType {
             ActionType::App => "App",
             ActionType::Url => "URL",
             ActionType::Shell => "Shell",
+            ActionType::Open => "Open",
+            ActionType::Shortcut => "Shortcut",
+            ActionType::AppleScript => "AppleScript",
+            ActionType::Delay => "Delay",
+            ActionType::Text => "Text",
+            ActionType::Keystroke => "Keystroke",
         }
     }
 
@@ -68,15 +103,27 @@ impl ActionType {
         match self {
             ActionType::App => ActionType::Url,
             ActionType::Url => ActionType::Shell,
-            ActionType::Shell => ActionType::App,
+            ActionType::Shell => ActionType::Open,
+            ActionType::Open => ActionType::Shortcut,
+            ActionType::Shortcut => ActionType::AppleScript,
+            ActionType::AppleScript => ActionType::Delay,
+            ActionType::Delay => ActionType::Text,
+            ActionType::Text => ActionType::Keystroke,
+            ActionType::Keystroke => ActionType::App,
         }
     }
 
     pub fn prev(&self) -> ActionType {
         match self {
-            ActionType::App => ActionType::Shell,
+            ActionType::App => ActionType::Keystroke,
             ActionType::Url => ActionType::App,
             ActionType::Shell => ActionType::Url,
+            ActionType::Open => ActionType::Shell,
+            ActionType::Shortcut => ActionType::Open,
+            ActionType::AppleScript => ActionType::Shortcut,
+            ActionType::Delay => ActionType::AppleScript,
+            ActionType::Text => ActionType::Delay,
+            ActionType::Keystroke => ActionType::Text,
         }
     }
 }
@@ -87,8 +134,17 @@ impl ActionEditor {
             action_type: ActionType::App,
             target: String::new(),
             bundle_id: None,
+            toggle: false,
             match_type: UrlMatchType::Domain,
+            url_options: UrlMatchOptions::default(),
+            url_option_cursor: 0,
             browser: None,
+            profile: None,
+            modifiers: Vec::new(),
+            modifier_cursor: 0,
+            input: String::new(),
+            script_from_file: false,
+            with_app: String::new(),
             field: ActionEditorField::Type,
             edit_mode: false,
         }
@@ -96,36 +152,79 @@ impl ActionEditor {
 
     pub fn from_action(action: &Action) -> Self {
         match action {
-            Action::App { target, bundle_id } => Self {
+            Action::App {
+                target,
+                bundle_id,
+                toggle,
+            } => Self {
                 action_type: ActionType::App,
                 target: target.clone(),
                 bundle_id: bundle_id.clone(),
-                match_type: UrlMatchType::Domain,
-                browser: None,
-                field: ActionEditorField::Type,
-                edit_mode: false,
+                toggle: *toggle,
+                ..Self::new()
             },
             Action::Url {
                 target,
                 match_type,
                 browser,
+                profile,
+                options,
             } => Self {
                 action_type: ActionType::Url,
                 target: target.clone(),
-                bundle_id: None,
                 match_type: match_type.clone(),
+                url_options: *options,
                 browser: browser.clone(),
-                field: ActionEditorField::Type,
-                edit_mode: false,
+                profile: profile.clone(),
+                ..Self::new()
             },
             Action::Shell { command } => Self {
                 action_type: ActionType::Shell,
                 target: command.clone(),
-                bundle_id: None,
-                match_type: UrlMatchType::Domain,
-                browser: None,
-                field: ActionEditorField::Type,
-                edit_mode: false,
+                ..Self::new()
+            },
+            Action::Open { path, with_app } => Self {
+                action_type: ActionType::Open,
+                target: path.clone(),
+                with_app: with_app.clone().unwrap_or_default(),
+                ..Self::new()
+            },
+            Action::Shortcut { name, input } => Self {
+                action_type: ActionType::Shortcut,
+                target: name.clone(),
+                input: input.clone().unwrap_or_default(),
+                ..Self::new()
+            },
+            Action::AppleScript { script } => {
+                let (target, script_from_file) = match script {
+                    AppleScript::Source(source) => (source.clone(), false),
+                    AppleScript::File(path) => (path.clone(), true),
+                };
+                Self {
+                    action_type: ActionType::AppleScript,
+                    target,
+                    script_from_file,
+                    ..Self::new()
+                }
+            }
+            Action::Delay { ms } => Self {
+                action_type: ActionType::Delay,
+                target: ms.to_string(),
+                ..Self::new()
+            },
+            Action::Text { text } => Self {
+                action_type: ActionType::Text,
+                target: text.clone(),
+                ..Self::new()
+            },
+            Action::Keystroke {
+                key_code,
+                modifiers,
+            } => Self {
+                action_type: ActionType::Keystroke,
+                target: key_code.clone(),
+                modifiers: modifiers.clone(),
+                ..Self::new()
             },
         }
     }
@@ -135,15 +234,65 @@ impl ActionEditor {
             ActionType::App => Action::App {
                 target: self.target.clone(),
                 bundle_id: self.bundle_id.clone(),
+                toggle: self.toggle,
             },
             ActionType::Url => Action::Url {
                 target: self.target.clone(),
                 match_type: self.match_type.clone(),
                 browser: self.browser.clone(),
+                profile: self.profile.clone(),
+                options: self.url_options,
             },
             ActionType::Shell => Action::Shell {
                 command: self.target.clone(),
             },
+            ActionType::Open => Action::Open {
+                path: self.target.clone(),
+                with_app: Some(self.with_app.clone()).filter(|id| !id.is_empty()),
+            },
+            ActionType::Shortcut => Action::Shortcut {
+                name: self.target.clone(),
+                input: Some(self.input.clone()).filter(|i| !i.is_empty()),
+            },
+            ActionType::AppleScript => Action::AppleScript {
+                script: if self.script_from_file {
+                    AppleScript::File(self.target.clone())
+                } else {
+                    AppleScript::Source(self.target.clone())
+                },
+            },
+            ActionType::Delay => Action::Delay {
+                ms: self.target.parse().unwrap_or(0),
+            },
+            ActionType::Text => Action::Text {
+                text: self.target.clone(),
+            },
+            ActionType::Keystroke => Action::Keystroke {
+                key_code: self.target.clone(),
+                // Keep the canonical cmd, shift, opt, ctl, fn order
+                modifiers: KeyModifier::all()
+                    .iter()
+                    .filter(|m| self.modifiers.contains(m))
+                    .copied()
+                    .collect(),
+            },
+        }
+    }
+
+    /// Inline AppleScript: Target is a multi-line body, where Enter adds a line and
+    /// Esc/Tab finish editing
+    pub fn has_script_body(&self) -> bool {
+        self.action_type == ActionType::AppleScript && !self.script_from_file
+    }
+
+    /// Toggle the modifier under the cursor in the Keystroke modifier list
+    pub fn toggle_modifier(&mut self) {
+        let modifier = KeyModifier::all()[self.modifier_cursor];
+        match self.modifiers.iter().position(|m| *m == modifier) {
+            Some(idx) => {
+                self.modifiers.remove(idx);
+            }
+            None => self.modifiers.push(modifier),
         }
     }
 
@@ -151,8 +300,15 @@ impl ActionEditor {
         self.field = match (&self.action_type, &self.field) {
             (_, ActionEditorField::Type) => ActionEditorField::Target,
             (ActionType::Url, ActionEditorField::Target) => ActionEditorField::MatchType,
-            (ActionType::Url, ActionEditorField::MatchType) => ActionEditorField::Browser,
-            (ActionType::Url, ActionEditorField::Browser) => ActionEditorField::Type,
+            (ActionType::Url, ActionEditorField::MatchType) => ActionEditorField::MatchOptions,
+            (ActionType::Url, ActionEditorField::MatchOptions) => ActionEditorField::Browser,
+            (ActionType::Url, ActionEditorField::Browser) => ActionEditorField::Profile,
+            (ActionType::Url, ActionEditorField::Profile) => ActionEditorField::Type,
+            (ActionType::Keystroke, ActionEditorField::Target) => ActionEditorField::Modifiers,
+            (ActionType::Shortcut, ActionEditorField::Target) => ActionEditorField::Input,
+            (ActionType::Open, ActionEditorField::Target) => ActionEditorField::WithApp,
+            (ActionType::App, ActionEditorField::Target) => ActionEditorField::Toggle,
+            (ActionType::AppleScript, ActionEditorField::Target) => ActionEditorField::ScriptSource,
             (_, ActionEditorField::Target) => ActionEditorField::Type,
             _ => ActionEditorField::Type,
         };
@@ -161,24 +317,43 @@ impl ActionEditor {
     pub fn prev_field(&mut self) {
         self.field = match (&self.action_type, &self.field) {
             (_, ActionEditorField::Type) => {
-                if self.action_type == ActionType::Url {
-                    ActionEditorField::Browser
-                } else {
-                    ActionEditorField::Target
+                match self.action_type {
+                    ActionType::Url => ActionEditorField::Profile,
+                    ActionType::Keystroke => ActionEditorField::Modifiers,
+                    ActionType::Shortcut => ActionEditorField::Input,
+                    ActionType::Open => ActionEditorField::WithApp,
+                    ActionType::App => ActionEditorField::Toggle,
+                    ActionType::AppleScript => ActionEditorField::ScriptSource,
+                    _ => ActionEditorField::Target,
                 }
             }
             (_, ActionEditorField::Target) => ActionEditorField::Type,
             (_, ActionEditorField::MatchType) => ActionEditorField::Target,
-            (_, ActionEditorField::Browser) => ActionEditorField::MatchType,
+            (_, ActionEditorField::MatchOptions) => ActionEditorField::MatchType,
+            (_, ActionEditorField::Browser) => ActionEditorField::MatchOptions,
+            (_, ActionEditorField::Profile) => ActionEditorField::Browser,
+            (_, ActionEditorField::Modifiers) => ActionEditorField::Target,
+            (_, ActionEditorField::Input) => ActionEditorField::Target,
+            (_, ActionEditorField::ScriptSource) => ActionEditorField::Target,
+            (_, ActionEditorField::WithApp) => ActionEditorField::Target,
+            (_, ActionEditorField::Toggle) => ActionEditorField::Target,
         };
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct BindingEditor {
+    pub base: Binding, // Original binding: keeps fields the editor doesn't show (e.g. modified actions)
     pub key: String,
+    pub sequence: String, // Second stroke of a leader sequence (empty = plain binding)
     pub description: String,
     pub actions: Vec<Action>,
+    pub only_in: Vec<String>,
+    pub except_in: Vec<String>,
+    pub app_input: String, // Pending text for the OnlyIn/ExceptIn fields
+    pub focus_aware: bool,
+    pub mode: ActionMode,
+    pub cycle_reset: String, // Digits only; empty = settings default
     pub selected_action: usize,
     pub field: EditorField,
     pub action_editor: Option<ActionEditor>,
@@ -189,9 +364,17 @@ pub struct BindingEditor {
 impl BindingEditor {
     pub fn new() -> Self {
         Self {
+            base: Binding::default(),
             key: String::new(),
+            sequence: String::new(),
             description: String::new(),
             actions: Vec::new(),
+            only_in: Vec::new(),
+            except_in: Vec::new(),
+            app_input: String::new(),
+            focus_aware: false,
+            mode: ActionMode::Cycle,
+            cycle_reset: String::new(),
             selected_action: 0,
             field: EditorField::Key,
             action_editor: None,
@@ -202,9 +385,20 @@ impl BindingEditor {
 
     pub fn from_binding(binding: &Binding) -> Self {
         Self {
+            base: binding.clone(),
             key: binding.key.clone(),
+            sequence: binding.sequence.clone().unwrap_or_default(),
             description: binding.description.clone(),
             actions: binding.actions.clone(),
+            only_in: binding.only_in.clone(),
+            except_in: binding.except_in.clone(),
+            app_input: String::new(),
+            focus_aware: binding.focus_aware,
+            mode: binding.mode,
+            cycle_reset: binding
+                .cycle_reset_ms
+                .map(|ms| ms.to_string())
+                .unwrap_or_default(),
             selected_action: 0,
             field: EditorField::Key,
             action_editor: None,
@@ -216,15 +410,26 @@ impl BindingEditor {
     pub fn to_binding(&self) -> Binding {
         Binding {
             key: self.key.clone(),
+            sequence: Some(self.sequence.clone()).filter(|s| !s.is_empty()),
             description: self.description.clone(),
             actions: self.actions.clone(),
+            only_in: self.only_in.clone(),
+            except_in: self.except_in.clone(),
+            focus_aware: self.focus_aware,
+            mode: self.mode,
+            cycle_reset_ms: self.cycle_reset.parse().ok(),
+            ..self.base.clone()
         }
     }
 
     pub fn next_field(&mut self) {
         self.field = match self.field {
-            EditorField::Key => EditorField::Description,
-            EditorField::Description => EditorField::Actions,
+            EditorField::Key => EditorField::Sequence,
+            EditorField::Sequence => EditorField::Description,
+            EditorField::Description => EditorField::OnlyIn,
+            EditorField::OnlyIn => EditorField::ExceptIn,
+            EditorField::ExceptIn => EditorField::CycleReset,
+            EditorField::CycleReset => EditorField::Actions,
             EditorField::Actions => EditorField::Key,
         };
     }
@@ -232,11 +437,46 @@ impl BindingEditor {
     pub fn prev_field(&mut self) {
         self.field = match self.field {
             EditorField::Key => EditorField::Actions,
-            EditorField::Description => EditorField::Key,
-            EditorField::Actions => EditorField::Description,
+            EditorField::Sequence => EditorField::Key,
+            EditorField::Description => EditorField::Sequence,
+            EditorField::OnlyIn => EditorField::Description,
+            EditorField::ExceptIn => EditorField::OnlyIn,
+            EditorField::CycleReset => EditorField::ExceptIn,
+            EditorField::Actions => EditorField::CycleReset,
         };
     }
 
+    /// Text of the focused Key/Sequence field
+    pub fn key_field_mut(&mut self) -> Option<&mut String> {
+        match self.field {
+            EditorField::Key => Some(&mut self.key),
+            EditorField::Sequence => Some(&mut self.sequence),
+            _ => None,
+        }
+    }
+
+    /// App list for the focused OnlyIn/ExceptIn field
+    pub fn app_list_mut(&mut self) -> Option<&mut Vec<String>> {
+        match self.field {
+            EditorField::OnlyIn => Some(&mut self.only_in),
+            EditorField::ExceptIn => Some(&mut self.except_in),
+            _ => None,
+        }
+    }
+
+    /// Add an entry (bundle ID or /regex/) to the focused app list, skipping duplicates
+    pub fn add_app_condition(&mut self, entry: String) {
+        let entry = entry.trim().to_string();
+        if entry.is_empty() {
+            return;
+        }
+        if let Some(list) = self.app_list_mut() {
+            if !list.contains(&entry) {
+                list.push(entry);
+            }
+        }
+    }
+
     pub fn start_adding_action(&mut self) {
         self.action_editor = Some(ActionEditor::new());
         self.editing_action_index = None;
@@ -297,6 +537,7 @@ pub enum SettingsField {
     AnchorKey,
     DefaultBrowser,
     CenterMouse,
+    Profiles,
 }
 
 pub struct App {
@@ -313,6 +554,8 @@ pub struct App {
 
     // Settings tab state
     pub settings_field: SettingsField,
+    pub karabiner_profiles: Vec<String>, // Profile names read from karabiner.json
+    pub profile_cursor: usize,
 
     // Autocomplete state
     pub autocomplete_suggestions: Vec<AutocompleteSuggestion>,
@@ -339,6 +582,8 @@ impl App {
             binding_editor: None,
             editing_binding_index: None,
             settings_field: SettingsField::AnchorKey,
+            karabiner_profiles: Vec::new(),
+            profile_cursor: 0,
             autocomplete_suggestions: Vec::new(),
             autocomplete_selected: 0,
             show_autocomplete: false,
@@ -504,14 +749,24 @@ impl App {
                 return;
             }
 
+            // Leader keys can't also be bound directly
             let binding = editor.to_binding();
-            if let Some(idx) = self.editing_binding_index.take() {
-                self.config.bindings[idx] = binding;
-            } else {
-                self.config.bindings.push(binding);
-                self.selected_binding = self.config.bindings.len() - 1;
+            let mut bindings = self.config.bindings.clone();
+            match self.editing_binding_index {
+                Some(idx) => bindings[idx] = binding,
+                None => bindings.push(binding),
+            }
+            if let Err(e) = crate::validation::check_bindings(&bindings) {
+                self.set_status(e.to_string());
+                self.binding_editor = Some(editor);
+                return;
             }
 
+            if self.editing_binding_index.take().is_none() {
+                self.selected_binding = bindings.len() - 1;
+            }
+            self.config.bindings = bindings;
+
             // Sort bindings alphabetically by key
             self.config.bindings.sort_by(|a, b| a.key.cmp(&b.key));
 
@@ -519,6 +774,55 @@ impl App {
         }
     }
 
+    /// Import hand-written Karabiner rules into the bindings list (saved with `s`)
+    pub fn import_from_karabiner(&mut self) {
+        use crate::karabiner::backup::karabiner_config_path;
+        use crate::karabiner::import::{import_rules, merge_imported};
+        use crate::karabiner::{load_karabiner_config, target_profile_indices};
+
+        let loaded = karabiner_config_path()
+            .and_then(|p| load_karabiner_config(&p))
+            .and_then(|c| {
+                let profile = target_profile_indices(&c, &self.config.settings.profiles)?[0];
+                Ok((c, profile))
+            });
+        let (kara_config, profile) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                self.set_status(format!("Import failed: {}", e));
+                return;
+            }
+        };
+
+        let report = import_rules(
+            &kara_config,
+            profile,
+            &self.config.settings.anchor_key,
+            &self.discovered_apps,
+        );
+        let imported = report.bindings.len();
+        let skipped = report.skipped.len();
+        let conflicts = merge_imported(&mut self.config, report.bindings).len();
+        self.generate_dynamic_bindings();
+
+        let added = imported - conflicts;
+        let mut msg = format!("Imported {} bindings", added);
+        if conflicts > 0 {
+            msg.push_str(&format!(", {} already bound", conflicts));
+        }
+        if skipped > 0 {
+            msg.push_str(&format!(
+                ", {} rules not convertible (details: rcmdb import --dry-run)",
+                skipped
+            ));
+        }
+        if added > 0 {
+            // The source rules stay in karabiner.json until removed there
+            msg.push_str(". Remove the original rules in Karabiner-Elements, or keys fire twice");
+        }
+        self.set_status(msg);
+    }
+
     pub fn cancel_edit(&mut self) {
         self.binding_editor = None;
         self.editing_binding_index = None;
@@ -541,6 +845,58 @@ impl App {
         self.show_autocomplete = !self.autocomplete_suggestions.is_empty() && !partial.is_empty();
     }
 
+    /// Autocomplete for an action's target: apps for App actions, key codes for Keystroke
+    pub fn update_target_autocomplete(&mut self, action_type: ActionType, partial: &str) {
+        match action_type {
+            ActionType::App => self.update_app_autocomplete(partial),
+            ActionType::Keystroke => self.update_autocomplete(partial),
+            ActionType::Open => self.update_path_autocomplete(partial),
+            _ => {}
+        }
+    }
+
+    /// Entries of the directory typed so far: "~/wo" suggests "~/work/"
+    pub fn update_path_autocomplete(&mut self, partial: &str) {
+        let (dir, prefix) = match partial.rfind('/') {
+            Some(i) => partial.split_at(i + 1),
+            None => ("", partial),
+        };
+        let entries = if dir.is_empty() {
+            None
+        } else {
+            std::fs::read_dir(expand_home(dir)).ok()
+        };
+        let mut names: Vec<(String, bool)> = entries
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                Some((name, entry.path().is_dir()))
+            })
+            // Hidden entries only once a "." is typed
+            .filter(|(name, _)| {
+                name.starts_with(prefix) && (prefix.starts_with('.') || !name.starts_with('.'))
+            })
+            .collect();
+        names.sort();
+
+        self.autocomplete_suggestions = names
+            .into_iter()
+            .take(10)
+            .map(|(name, is_dir)| {
+                let value = format!("{}{}{}", dir, name, if is_dir { "/" } else { "" });
+                AutocompleteSuggestion {
+                    display: value.clone(),
+                    value,
+                    bundle_id: String::new(),
+                }
+            })
+            .collect();
+        self.autocomplete_selected = 0;
+        self.show_autocomplete = !self.autocomplete_suggestions.is_empty();
+    }
+
     pub fn update_app_autocomplete(&mut self, partial: &str) {
         use crate::app_discovery::extract_parent_component;
 
@@ -626,7 +982,7 @@ impl App {
             .collect();
 
         // Sort by name (case-insensitive)
-        merged.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        merged.sort_by_key(|a| a.name.to_lowercase());
 
         self.discovered_apps = merged.clone();
         self.config.cached_apps = merged;
@@ -668,7 +1024,9 @@ impl App {
                     actions: vec![Action::App {
                         target: app.name.clone(),
                         bundle_id: Some(app.bundle_id.clone()),
+                        toggle: false,
                     }],
+                    ..Default::default()
                 };
                 dynamics.push(binding);
             }
@@ -705,7 +1063,7 @@ impl App {
 
     // Settings navigation
     pub fn next_browser(&mut self) {
-        let browsers = Browser::all();
+        let browsers = self.config.browser_choices();
         let current_idx = browsers
             .iter()
             .position(|b| b == &self.config.settings.default_browser)
@@ -715,7 +1073,7 @@ impl App {
     }
 
     pub fn prev_browser(&mut self) {
-        let browsers = Browser::all();
+        let browsers = self.config.browser_choices();
         let current_idx = browsers
             .iter()
             .position(|b| b == &self.config.settings.default_browser)
@@ -742,15 +1100,74 @@ impl App {
         self.config.settings.anchor_key = keys[idx.checked_sub(1).unwrap_or(keys.len() - 1)].clone();
     }
 
+    /// Only meaningful with the Hyper anchor
+    pub fn toggle_caps_lock_hyper(&mut self) {
+        let settings = &mut self.config.settings;
+        if settings.anchor_key.is_hyper() {
+            settings.caps_lock_hyper = !settings.caps_lock_hyper;
+        }
+    }
+
     pub fn toggle_center_mouse(&mut self) {
         self.config.settings.center_mouse = self.config.settings.center_mouse.cycle();
     }
 
+    /// Read profile names from karabiner.json for the profile picker (silent on failure)
+    pub fn load_karabiner_profiles(&mut self) {
+        use crate::karabiner::backup::karabiner_config_path;
+        use crate::karabiner::{load_karabiner_config, profile_names};
+
+        self.karabiner_profiles = karabiner_config_path()
+            .and_then(|p| load_karabiner_config(&p))
+            .map(|c| profile_names(&c))
+            .unwrap_or_default();
+    }
+
+    /// Profiles shown in the picker: those in karabiner.json, plus configured ones
+    /// that no longer exist there (so they can be deselected)
+    pub fn profile_choices(&self) -> Vec<String> {
+        let mut choices = self.karabiner_profiles.clone();
+        for name in &self.config.settings.profiles {
+            if !choices.contains(name) {
+                choices.push(name.clone());
+            }
+        }
+        choices
+    }
+
+    pub fn next_profile(&mut self) {
+        let count = self.profile_choices().len();
+        if count > 0 {
+            self.profile_cursor = (self.profile_cursor + 1) % count;
+        }
+    }
+
+    pub fn prev_profile(&mut self) {
+        let count = self.profile_choices().len();
+        if count > 0 {
+            self.profile_cursor = self.profile_cursor.checked_sub(1).unwrap_or(count - 1);
+        }
+    }
+
+    pub fn toggle_profile(&mut self) {
+        let Some(name) = self.profile_choices().get(self.profile_cursor).cloned() else {
+            return;
+        };
+        let profiles = &mut self.config.settings.profiles;
+        match profiles.iter().position(|p| *p == name) {
+            Some(idx) => {
+                profiles.remove(idx);
+            }
+            None => profiles.push(name),
+        }
+    }
+
     pub fn next_settings_field(&mut self) {
         self.settings_field = match self.settings_field {
             SettingsField::AnchorKey => SettingsField::DefaultBrowser,
             SettingsField::DefaultBrowser => SettingsField::CenterMouse,
-            SettingsField::CenterMouse => SettingsField::AnchorKey,
+            SettingsField::CenterMouse => SettingsField::Profiles,
+            SettingsField::Profiles => SettingsField::AnchorKey,
         };
     }
 }
@@ -38,7 +38,7 @@ impl DiscoveredApp {
 /// Extract last component from bundle ID
 /// e.g., "com.kakaoenterprise.macos.kakaowork" -> "kakaowork"
 fn extract_last_component(bundle_id: &str) -> String {
-    bundle_id.split('.').last().unwrap_or(bundle_id).to_string()
+    bundle_id.split('.').next_back().unwrap_or(bundle_id).to_string()
 }
 
 /// Extract parent domain from bundle ID for disambiguation
@@ -132,7 +132,7 @@ pub fn discover_installed_apps() -> Vec<DiscoveredApp> {
 /// Read CFBundleIdentifier from Info.plist using plutil
 fn read_bundle_id_from_plist(plist_path: &std::path::Path) -> Result<String, std::io::Error> {
     let output = Command::new("plutil")
-        .args(&["-extract", "CFBundleIdentifier", "raw", "-o", "-"])
+        .args(["-extract", "CFBundleIdentifier", "raw", "-o", "-"])
         .arg(plist_path)
         .output()?;
 
@@ -177,7 +177,7 @@ pub fn discover_all_apps() -> Vec<DiscoveredApp> {
     }
 
     // Sort by display name (case-insensitive)
-    result.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    result.sort_by_key(|a| a.name.to_lowercase());
     result
 }
 
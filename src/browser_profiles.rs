@@ -0,0 +1,125 @@
+//! Chromium profiles, as listed in the `Local State` file of a browser's user data
+//! directory. URL actions name a profile; focus-url launches it by directory.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::config::{expand_home, Browser, BrowserDefinition, Config};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrowserProfile {
+    /// Directory in the user data directory ("Default", "Profile 1"), for --profile-directory
+    pub directory: String,
+    /// Name shown in the browser's profile menu
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+struct LocalState {
+    #[serde(default)]
+    profile: ProfileSection,
+}
+
+#[derive(Deserialize, Default)]
+struct ProfileSection {
+    #[serde(default)]
+    info_cache: BTreeMap<String, ProfileInfo>,
+}
+
+#[derive(Deserialize)]
+struct ProfileInfo {
+    #[serde(default)]
+    name: String,
+}
+
+/// Profiles in a `Local State` document, ordered by directory
+pub fn parse_local_state(json: &str) -> Result<Vec<BrowserProfile>> {
+    let state: LocalState = serde_json::from_str(json).context("Failed to parse Local State")?;
+    Ok(state
+        .profile
+        .info_cache
+        .into_iter()
+        .map(|(directory, info)| BrowserProfile {
+            name: if info.name.is_empty() {
+                directory.clone()
+            } else {
+                info.name
+            },
+            directory,
+        })
+        .collect())
+}
+
+/// Profiles of `browser`; none when it has no user data directory
+pub fn browser_profiles(browser: &BrowserDefinition) -> Result<Vec<BrowserProfile>> {
+    let Some(dir) = &browser.user_data_dir else {
+        return Ok(Vec::new());
+    };
+    let path = expand_home(dir).join("Local State");
+    let json = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {:?}", path))?;
+    parse_local_state(&json).with_context(|| format!("In {:?}", path))
+}
+
+/// Profile names to offer for a URL action's browser (None = the default browser).
+/// Empty when the browser has no readable Local State.
+pub fn profile_names(config: &Config, browser: Option<&Browser>) -> Vec<String> {
+    let browser = browser.unwrap_or(&config.settings.default_browser);
+    config
+        .browser_definition(browser)
+        .and_then(|definition| browser_profiles(&definition).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|profile| profile.name)
+        .collect()
+}
+
+/// The profile `wanted` refers to, by name or by directory
+pub fn find_profile<'a>(profiles: &'a [BrowserProfile], wanted: &str) -> Option<&'a BrowserProfile> {
+    profiles
+        .iter()
+        .find(|p| p.name == wanted)
+        .or_else(|| profiles.iter().find(|p| p.directory == wanted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCAL_STATE: &str = r#"{
+        "browser": { "enabled_labs_experiments": [] },
+        "profile": {
+            "info_cache": {
+                "Profile 1": { "name": "Work", "gaia_name": "A" },
+                "Default": { "name": "Personal" },
+                "Profile 3": {}
+            },
+            "last_used": "Profile 1"
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_local_state() {
+        let profiles = parse_local_state(LOCAL_STATE).unwrap();
+        let names: Vec<(&str, &str)> = profiles
+            .iter()
+            .map(|p| (p.directory.as_str(), p.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("Default", "Personal"),
+                ("Profile 1", "Work"),
+                ("Profile 3", "Profile 3")
+            ]
+        );
+        assert!(parse_local_state("{}").unwrap().is_empty());
+        assert!(parse_local_state("not json").is_err());
+
+        assert_eq!(find_profile(&profiles, "Work").unwrap().directory, "Profile 1");
+        assert_eq!(find_profile(&profiles, "Default").unwrap().name, "Personal");
+        assert!(find_profile(&profiles, "School").is_none());
+    }
+}
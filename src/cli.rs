@@ -0,0 +1,60 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use crate::commands::focus_url::FocusUrlArgs;
+
+/// TUI for configuring Karabiner-Elements right_command key bindings
+#[derive(Debug, Parser)]
+#[command(name = "rcmdb", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Regenerate karabiner.json from config.toml without opening the TUI
+    Apply,
+    /// Show what `apply` would change in karabiner.json without writing it
+    Diff(DiffArgs),
+    /// Import existing Karabiner rules into config.toml
+    Import(ImportArgs),
+    /// Focus the browser tab matching a URL, or open it (run by URL bindings)
+    FocusUrl(FocusUrlArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    /// Report what would be imported without saving config.toml
+    #[arg(long)]
+    pub dry_run: bool,
+    /// karabiner.json to read (defaults to ~/.config/karabiner/karabiner.json)
+    #[arg(long, value_name = "PATH")]
+    pub karabiner: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = DiffFormat::Summary)]
+    pub format: DiffFormat,
+    /// Exit with status 1 when karabiner.json is out of date
+    #[arg(long)]
+    pub check: bool,
+    /// config.toml to read (defaults to ~/.config/karabiner-rcmd-binder/config.toml)
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// karabiner.json to compare against (defaults to ~/.config/karabiner/karabiner.json)
+    #[arg(long, value_name = "PATH")]
+    pub karabiner: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum DiffFormat {
+    /// Added, removed and changed [rcmdb] rules and manipulators
+    Summary,
+    /// Unified text diff of karabiner.json
+    Unified,
+    /// RFC 6902 JSON patch
+    Json,
+}
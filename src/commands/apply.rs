@@ -0,0 +1,38 @@
+use anyhow::{Context, Result};
+
+use crate::config::{load_config, save_config, Config};
+use crate::karabiner::apply_to_karabiner;
+use crate::scripts::install_scripts;
+use crate::validation::{self, ValidationReport};
+
+/// Full save pipeline shared by the TUI and `rcmdb apply`:
+/// validate (resolving bundle IDs), save config.toml, install scripts, write karabiner.json.
+/// Returns what validation resolved and warned about.
+pub fn apply_config(config: &mut Config) -> Result<ValidationReport> {
+    let report =
+        validation::validate_and_update_config(config).context("Validation failed")?;
+
+    save_config(config)?;
+    install_scripts().context("Failed to install helper scripts")?;
+    apply_to_karabiner(config)?;
+
+    Ok(report)
+}
+
+/// `rcmdb apply`: run the save pipeline headlessly
+pub fn run() -> Result<()> {
+    let mut config = load_config()?;
+    let report = apply_config(&mut config)?;
+
+    for resolved in &report.resolved {
+        println!("{}", resolved);
+    }
+    for warning in &report.warnings {
+        eprintln!("{}", warning);
+    }
+    println!(
+        "Applied {} bindings to karabiner.json (backup created)",
+        config.bindings.len()
+    );
+    Ok(())
+}
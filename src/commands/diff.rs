@@ -0,0 +1,57 @@
+use anyhow::{Context, Result};
+use std::process::ExitCode;
+
+use crate::cli::{DiffArgs, DiffFormat};
+use crate::config::{config_path, load_config_from};
+use crate::karabiner::backup::karabiner_config_path;
+use crate::karabiner::diff::{diff_rules, format_summary, json_patch, unified_diff};
+use crate::karabiner::{build_karabiner_config, load_karabiner_config};
+use crate::validation::validate_and_update_config;
+
+/// `rcmdb diff`: compute the document `apply` would write and compare it with the current one.
+/// Validates and resolves bundle IDs in memory like `apply`, but never writes anything.
+pub fn run(args: &DiffArgs) -> Result<ExitCode> {
+    let config_file = match &args.config {
+        Some(path) => path.clone(),
+        None => config_path()?,
+    };
+    let karabiner_file = match &args.karabiner {
+        Some(path) => path.clone(),
+        None => karabiner_config_path()?,
+    };
+
+    let mut config = load_config_from(&config_file)?;
+    let report = validate_and_update_config(&mut config).context("Validation failed")?;
+    // stderr, so the diff on stdout stays machine-readable
+    for message in report.resolved.iter().chain(&report.warnings) {
+        eprintln!("{}", message);
+    }
+    let current = load_karabiner_config(&karabiner_file)?;
+    let proposed = build_karabiner_config(&current, &config)?;
+
+    let changed = current != proposed;
+
+    match args.format {
+        DiffFormat::Summary => {
+            let diffs = diff_rules(&current, &proposed);
+            if !changed {
+                println!("No changes");
+            } else if diffs.is_empty() {
+                // e.g. a missing profile or complex_modifications section gets created
+                println!("Structural changes outside [rcmdb] rules (see --format unified)");
+            } else {
+                print!("{}", format_summary(&diffs));
+            }
+        }
+        DiffFormat::Unified => print!("{}", unified_diff(&current, &proposed)),
+        DiffFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&json_patch(&current, &proposed))?
+        ),
+    }
+
+    if args.check && changed {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
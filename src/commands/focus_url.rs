@@ -0,0 +1,572 @@
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use regex::Regex;
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::process::Command;
+use url::Url;
+
+use crate::browser_profiles::{browser_profiles, find_profile, BrowserProfile};
+use crate::config::{
+    load_config, Browser, BrowserDefinition, BrowserFamily, UrlMatchOptions, UrlMatchType,
+};
+use crate::url_match::url_regex;
+
+/// Prints the open tabs as JSON, front window first: `[{window, index, url, window_title}]`.
+/// Does not launch the browser when it isn't running.
+const LIST_TABS_SCRIPT: &str = r#"
+function run(argv) {
+    var browser = Application(argv[0]);
+    if (!browser.running()) {
+        return '[]';
+    }
+    var tabs = [];
+    browser.windows().forEach(function (window, w) {
+        var title = window.name() || '';
+        window.tabs().forEach(function (tab, t) {
+            tabs.push({ window: w, index: t, url: tab.url() || '', window_title: title });
+        });
+    });
+    return JSON.stringify(tabs);
+}
+"#;
+
+/// Selects tab argv[2] of window argv[1] by index and brings both to the front
+const CHROMIUM_ACTIVATE_SCRIPT: &str = r#"
+function run(argv) {
+    var browser = Application(argv[0]);
+    var window = browser.windows[Number(argv[1])];
+    window.activeTabIndex = Number(argv[2]) + 1;
+    window.index = 1;
+    browser.activate();
+}
+"#;
+
+/// Selects tab argv[2] of window argv[1] by reference and brings both to the front
+const SAFARI_ACTIVATE_SCRIPT: &str = r#"
+function run(argv) {
+    var browser = Application(argv[0]);
+    var window = browser.windows[Number(argv[1])];
+    window.currentTab = window.tabs[Number(argv[2])];
+    window.index = 1;
+    browser.activate();
+}
+"#;
+
+/// An open tab. Both indices count from 0; windows in front-to-back order.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Tab {
+    pub window: usize,
+    pub index: usize,
+    pub url: String,
+    #[serde(default)]
+    pub window_title: String,
+}
+
+/// Where `focus-url` gets tabs from and how it acts on its choice
+pub trait TabSource {
+    fn tabs(&self) -> Result<Vec<Tab>>;
+    fn activate(&self, tab: &Tab) -> Result<()>;
+    fn open(&self, url: &str) -> Result<()>;
+}
+
+/// A browser driven through osascript (JXA). Values are passed as arguments,
+/// never spliced into script source.
+pub struct BrowserTabs {
+    /// Bundle ID or application name
+    application: String,
+    by_bundle_id: bool,
+    family: BrowserFamily,
+    /// Profile to search and open in, with every profile's name to tell windows apart
+    profile: Option<(BrowserProfile, Vec<String>)>,
+}
+
+impl BrowserTabs {
+    pub fn new(definition: &BrowserDefinition) -> Result<Self> {
+        let application = definition
+            .application()
+            .with_context(|| format!("Browser '{}' has no app or bundle_id", definition.name))?;
+        Ok(Self {
+            application: application.to_string(),
+            by_bundle_id: definition.bundle_id.as_deref() == Some(application),
+            family: definition.family,
+            profile: None,
+        })
+    }
+
+    /// Only tabs in `profile`'s windows, and URLs opened with its --profile-directory
+    pub fn with_profile(self, profile: BrowserProfile, all_names: Vec<String>) -> Self {
+        Self {
+            profile: Some((profile, all_names)),
+            ..self
+        }
+    }
+
+    fn open_args(&self, url: &str) -> Vec<String> {
+        let flag = if self.by_bundle_id { "-b" } else { "-a" };
+        let mut args = vec![flag.to_string(), self.application.clone()];
+        if let Some((profile, _)) = &self.profile {
+            // -n launches with the arguments; Chromium hands them to the running instance
+            args.insert(0, "-n".to_string());
+            args.push("--args".to_string());
+            args.push(format!("--profile-directory={}", profile.directory));
+        }
+        args.push(url.to_string());
+        args
+    }
+}
+
+impl TabSource for BrowserTabs {
+    fn tabs(&self) -> Result<Vec<Tab>> {
+        if self.family == BrowserFamily::Firefox {
+            return Ok(Vec::new());
+        }
+        let output = osascript(LIST_TABS_SCRIPT, &[&self.application])?;
+        let tabs = serde_json::from_str(&output)
+            .with_context(|| format!("Unexpected tab list from {}: {}", self.application, output))?;
+        Ok(match &self.profile {
+            Some((profile, names)) => in_profile(tabs, &profile.name, names),
+            None => tabs,
+        })
+    }
+
+    fn activate(&self, tab: &Tab) -> Result<()> {
+        let script = match self.family {
+            BrowserFamily::Safari => SAFARI_ACTIVATE_SCRIPT,
+            _ => CHROMIUM_ACTIVATE_SCRIPT,
+        };
+        let (window, index) = (tab.window.to_string(), tab.index.to_string());
+        osascript(script, &[&self.application, &window, &index]).map(|_| ())
+    }
+
+    fn open(&self, url: &str) -> Result<()> {
+        let args = self.open_args(url);
+        let status = Command::new("open")
+            .args(&args)
+            .status()
+            .context("Failed to run open")?;
+        if !status.success() {
+            bail!("open {} failed ({})", args.join(" "), status);
+        }
+        Ok(())
+    }
+}
+
+/// Run a JXA script with `args` as argv and return its trimmed output
+fn osascript(script: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new("osascript")
+        .args(["-l", "JavaScript", "-e", script])
+        .args(args)
+        .output()
+        .context("Failed to run osascript")?;
+    if !output.status.success() {
+        bail!(
+            "osascript failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Arguments of `rcmdb focus-url`, also read back from generated rules by import
+#[derive(Debug, Parser)]
+#[command(name = "focus-url")]
+pub struct FocusUrlArgs {
+    /// URL to focus, or open when no tab matches
+    pub url: String,
+    /// How tab URLs are compared: exact, domain, path, glob or regex
+    #[arg(long, value_parser = parse_match_type, default_value = "exact")]
+    pub match_type: UrlMatchType,
+    /// Browser to search: firefox, chrome, safari, arc, edge or a [[browsers]] name
+    #[arg(long)]
+    pub browser: Browser,
+    /// Chromium profile (name or directory) to search and open the URL in
+    #[arg(long)]
+    pub profile: Option<String>,
+    /// Match tabs whatever their query string
+    #[arg(long)]
+    pub ignore_query: bool,
+    /// Match tabs whatever their #fragment
+    #[arg(long)]
+    pub ignore_fragment: bool,
+    /// Match tabs with or without a trailing slash
+    #[arg(long)]
+    pub ignore_trailing_slash: bool,
+}
+
+impl FocusUrlArgs {
+    pub fn options(&self) -> UrlMatchOptions {
+        UrlMatchOptions {
+            ignore_query: self.ignore_query,
+            ignore_fragment: self.ignore_fragment,
+            ignore_trailing_slash: self.ignore_trailing_slash,
+        }
+    }
+}
+
+/// Parse the arguments of a `focus-url` command line, after the subcommand name.
+/// None when the CLI would reject them.
+pub fn parse_args(args: &[&str]) -> Option<FocusUrlArgs> {
+    FocusUrlArgs::try_parse_from(std::iter::once("focus-url").chain(args.iter().copied())).ok()
+}
+
+fn parse_match_type(s: &str) -> Result<UrlMatchType, String> {
+    UrlMatchType::all()
+        .iter()
+        .find(|t| t.as_str() == s)
+        .cloned()
+        .ok_or_else(|| format!("unknown match type '{}'", s))
+}
+
+/// `rcmdb focus-url`: what URL bindings run when pressed. Custom browsers are
+/// looked up in config.toml's `[[browsers]]`.
+pub fn run(args: &FocusUrlArgs) -> Result<()> {
+    let definition = match args.browser.builtin_definition() {
+        Some(definition) => definition,
+        None => load_config()?
+            .browser_definition(&args.browser)
+            .with_context(|| format!("No [[browsers]] entry named '{}'", args.browser.as_str()))?,
+    };
+    let mut source = BrowserTabs::new(&definition)?;
+    if let Some(wanted) = &args.profile {
+        if definition.family != BrowserFamily::Chromium {
+            bail!("Browser '{}' has no profiles to choose from", definition.name);
+        }
+        let profiles = browser_profiles(&definition).unwrap_or_else(|err| {
+            eprintln!("rcmdb focus-url: {:#}", err);
+            Vec::new()
+        });
+        // A profile missing from Local State is tried as a directory name
+        let profile = find_profile(&profiles, wanted)
+            .cloned()
+            .unwrap_or_else(|| BrowserProfile {
+                directory: wanted.clone(),
+                name: wanted.clone(),
+            });
+        let names = profiles.into_iter().map(|p| p.name).collect();
+        source = source.with_profile(profile, names);
+    }
+    focus_url(&source, &args.url, &args.match_type, &args.options())
+}
+
+/// Tabs in windows of `profile`. Chromium's scripting has no profile property, but
+/// once several profiles exist, window titles end with " - <profile name>". This
+/// assumes page titles don't end that way themselves. A title can end in several
+/// names ("Work" and "Team - Work"); the longest one is its profile. When no title
+/// names a profile the windows can't be told apart, and all are kept.
+fn in_profile(tabs: Vec<Tab>, profile: &str, all_names: &[String]) -> Vec<Tab> {
+    let mut names: Vec<&str> = all_names.iter().map(String::as_str).collect();
+    names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+    let owner = |tab: &Tab| {
+        names.iter().copied().find(|name| {
+            tab.window_title
+                .strip_suffix(name)
+                .is_some_and(|rest| rest.ends_with(" - "))
+        })
+    };
+    if !tabs.iter().any(|tab| owner(tab).is_some()) {
+        return tabs;
+    }
+    tabs.into_iter()
+        .filter(|tab| owner(tab) == Some(profile))
+        .collect()
+}
+
+/// Activate the best tab matching `target`, or open it. A tab list that can't be
+/// read (e.g. automation permission denied) is reported and treated as empty,
+/// so the key still opens the URL.
+pub fn focus_url(
+    source: &dyn TabSource,
+    target: &str,
+    match_type: &UrlMatchType,
+    options: &UrlMatchOptions,
+) -> Result<()> {
+    let regex = url_regex(target, match_type, options)
+        .with_context(|| format!("Invalid URL pattern '{}'", target))?;
+    let target_url = parse_target(target);
+
+    let tabs = source.tabs().unwrap_or_else(|err| {
+        eprintln!("rcmdb focus-url: {:#}", err);
+        Vec::new()
+    });
+    match best_tab(&tabs, &regex, target_url.as_ref()) {
+        Some(tab) => source.activate(tab),
+        None => source.open(target_url.as_ref().map_or(target, Url::as_str)),
+    }
+}
+
+/// The target as a URL. Targets without a scheme ("github.com/org") are https.
+fn parse_target(target: &str) -> Option<Url> {
+    if target.contains("://") {
+        Url::parse(target).ok()
+    } else {
+        Url::parse(&format!("https://{}", target)).ok()
+    }
+}
+
+/// Of the tabs the pattern accepts, the one closest to the target: the same URL
+/// first, then the most leading path segments in common, then the frontmost
+fn best_tab<'a>(tabs: &'a [Tab], regex: &Regex, target: Option<&Url>) -> Option<&'a Tab> {
+    tabs.iter()
+        .filter(|tab| regex.is_match(&tab.url))
+        .min_by_key(|tab| Reverse(closeness(target, &tab.url)))
+}
+
+fn closeness(target: Option<&Url>, url: &str) -> (bool, usize) {
+    let (Some(target), Ok(url)) = (target, Url::parse(url)) else {
+        return (false, 0);
+    };
+    let common = match (target.path_segments(), url.path_segments()) {
+        (Some(a), Some(b)) => a
+            .zip(b)
+            .take_while(|(a, b)| a == b && !a.is_empty())
+            .count(),
+        _ => 0,
+    };
+    (*target == url, common)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Browser;
+    use std::cell::RefCell;
+
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/browser-tabs.json");
+    const OVERLAPPING_PROFILES: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/browser-tabs-overlapping-profiles.json"
+    );
+
+    /// Tabs from a fixture file; records what focus_url does with them
+    struct FixtureTabs {
+        tabs: Option<Vec<Tab>>,
+        calls: RefCell<Vec<String>>,
+    }
+
+    impl FixtureTabs {
+        fn load() -> Self {
+            Self::load_from(FIXTURE)
+        }
+
+        fn load_from(path: &str) -> Self {
+            let json = std::fs::read_to_string(path).unwrap();
+            Self {
+                tabs: Some(serde_json::from_str(&json).unwrap()),
+                calls: RefCell::default(),
+            }
+        }
+
+        fn focus(&self, target: &str, match_type: UrlMatchType) -> Vec<String> {
+            self.focus_with(target, match_type, UrlMatchOptions::default())
+        }
+
+        fn focus_with(
+            &self,
+            target: &str,
+            match_type: UrlMatchType,
+            options: UrlMatchOptions,
+        ) -> Vec<String> {
+            self.calls.borrow_mut().clear();
+            focus_url(self, target, &match_type, &options).unwrap();
+            self.calls.borrow().clone()
+        }
+    }
+
+    impl TabSource for FixtureTabs {
+        fn tabs(&self) -> Result<Vec<Tab>> {
+            self.tabs.clone().context("permission denied")
+        }
+
+        fn activate(&self, tab: &Tab) -> Result<()> {
+            self.calls
+                .borrow_mut()
+                .push(format!("activate {} {}", tab.window, tab.index));
+            Ok(())
+        }
+
+        fn open(&self, url: &str) -> Result<()> {
+            self.calls.borrow_mut().push(format!("open {}", url));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_focus_exact() {
+        let source = FixtureTabs::load();
+        assert_eq!(
+            source.focus("https://github.com/org/repo/pulls", UrlMatchType::Exact),
+            ["activate 1 0"]
+        );
+        assert_eq!(
+            source.focus("https://github.com/org/repo/issues", UrlMatchType::Exact),
+            ["open https://github.com/org/repo/issues"]
+        );
+        let loose = UrlMatchOptions {
+            ignore_query: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            source.focus_with("https://mail.example.com/inbox", UrlMatchType::Exact, loose),
+            ["activate 0 2"]
+        );
+    }
+
+    #[test]
+    fn test_focus_prefers_closest_tab() {
+        let source = FixtureTabs::load();
+        // Every github.com tab matches; the one sharing most of the path wins
+        assert_eq!(
+            source.focus("github.com/org/repo/pulls", UrlMatchType::Domain),
+            ["activate 1 0"]
+        );
+        assert_eq!(
+            source.focus("github.com/org/repo", UrlMatchType::Path),
+            ["activate 0 1"],
+            "the same URL beats a deeper one"
+        );
+        // No path in common: the frontmost match
+        assert_eq!(
+            source.focus("https://github.com/", UrlMatchType::Domain),
+            ["activate 0 0"]
+        );
+        assert_eq!(
+            source.focus(r"github\.com/org/repo/.+", UrlMatchType::Regex),
+            ["activate 1 0"]
+        );
+    }
+
+    #[test]
+    fn test_focus_opens_when_nothing_matches() {
+        let source = FixtureTabs::load();
+        assert_eq!(
+            source.focus("docs.rs/regex", UrlMatchType::Path),
+            ["open https://docs.rs/regex"],
+            "a target without a scheme opens as https"
+        );
+
+        let denied = FixtureTabs {
+            tabs: None,
+            calls: RefCell::default(),
+        };
+        assert_eq!(
+            denied.focus("https://github.com/org/repo", UrlMatchType::Exact),
+            ["open https://github.com/org/repo"]
+        );
+    }
+
+    #[test]
+    fn test_focus_in_profile() {
+        let names = ["Work".to_string(), "Personal".to_string()];
+        let tabs = FixtureTabs::load().tabs.unwrap();
+        let gmail = |profile: &str| FixtureTabs {
+            tabs: Some(in_profile(tabs.clone(), profile, &names)),
+            calls: RefCell::default(),
+        };
+        assert_eq!(
+            gmail("Personal").focus("mail.google.com", UrlMatchType::Domain),
+            ["activate 1 2"]
+        );
+        assert_eq!(
+            gmail("Work").focus("mail.google.com", UrlMatchType::Domain),
+            ["activate 0 3"]
+        );
+        assert_eq!(
+            gmail("Work").focus("docs.rs", UrlMatchType::Domain),
+            ["open https://docs.rs/"],
+            "docs.rs is only open in Personal"
+        );
+
+        // Titles that name no known profile: every window is searched
+        let other = ["School".to_string()];
+        assert_eq!(in_profile(tabs.clone(), "School", &other), tabs);
+    }
+
+    #[test]
+    fn test_focus_in_overlapping_profiles() {
+        // "Inbox - Work" and "Team Wiki - Team - Work" both end in " - Work"
+        let names = ["Work", "Home Work", "Team - Work"].map(String::from);
+        let tabs = FixtureTabs::load_from(OVERLAPPING_PROFILES).tabs.unwrap();
+        let gmail = |profile: &str| FixtureTabs {
+            tabs: Some(in_profile(tabs.clone(), profile, &names)),
+            calls: RefCell::default(),
+        };
+        for (profile, activated) in [
+            ("Work", "activate 1 0"),
+            ("Team - Work", "activate 0 1"),
+            ("Home Work", "activate 2 1"),
+        ] {
+            assert_eq!(
+                gmail(profile).focus("mail.google.com", UrlMatchType::Domain),
+                [activated],
+                "{}",
+                profile
+            );
+        }
+        let windows = |profile: &str| -> Vec<usize> {
+            in_profile(tabs.clone(), profile, &names).iter().map(|t| t.window).collect()
+        };
+        assert_eq!(windows("Work"), [1]);
+        assert_eq!(windows("Team - Work"), [0, 0]);
+    }
+
+    #[test]
+    fn test_profile_launch_arguments() {
+        let chrome = Browser::Chrome.builtin_definition().unwrap();
+        let tabs = BrowserTabs::new(&chrome).unwrap();
+        assert_eq!(tabs.open_args("https://a.com"), ["-a", "Google Chrome", "https://a.com"]);
+        let work = BrowserProfile {
+            directory: "Profile 1".to_string(),
+            name: "Work".to_string(),
+        };
+        let tabs = tabs.with_profile(work, Vec::new());
+        assert_eq!(
+            tabs.open_args("https://a.com"),
+            [
+                "-n",
+                "-a",
+                "Google Chrome",
+                "--args",
+                "--profile-directory=Profile 1",
+                "https://a.com"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_browser_tabs_from_definitions() {
+        let orion = BrowserDefinition {
+            name: "orion".to_string(),
+            app: Some("Orion".to_string()),
+            bundle_id: Some("com.kagi.kagimacOS".to_string()),
+            family: BrowserFamily::Safari,
+            user_data_dir: None,
+        };
+        let tabs = BrowserTabs::new(&orion).unwrap();
+        assert_eq!(tabs.application, "com.kagi.kagimacOS");
+        assert!(tabs.by_bundle_id);
+
+        let tabs = BrowserTabs::new(&BrowserDefinition {
+            bundle_id: None,
+            ..orion.clone()
+        })
+        .unwrap();
+        assert_eq!(tabs.application, "Orion");
+        assert!(!tabs.by_bundle_id);
+
+        assert!(BrowserTabs::new(&BrowserDefinition {
+            app: None,
+            bundle_id: None,
+            ..orion
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_jxa_scripts_take_values_as_arguments() {
+        for script in [LIST_TABS_SCRIPT, CHROMIUM_ACTIVATE_SCRIPT, SAFARI_ACTIVATE_SCRIPT] {
+            assert!(script.contains("function run(argv)"));
+            assert!(!script.contains("{}"), "no format! placeholders");
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use anyhow::Result;
+
+use crate::cli::ImportArgs;
+use crate::config::{load_config, save_config};
+use crate::karabiner::backup::karabiner_config_path;
+use crate::karabiner::import::{import_rules, merge_imported};
+use crate::karabiner::{load_karabiner_config, target_profile_indices};
+
+/// `rcmdb import`: convert hand-written Karabiner rules into bindings in config.toml
+pub fn run(args: &ImportArgs) -> Result<()> {
+    let karabiner_file = match &args.karabiner {
+        Some(path) => path.clone(),
+        None => karabiner_config_path()?,
+    };
+
+    let mut config = load_config()?;
+    let kara_config = load_karabiner_config(&karabiner_file)?;
+    // Import from the first profile rcmdb writes into
+    let profile = target_profile_indices(&kara_config, &config.settings.profiles)?[0];
+    let report = import_rules(
+        &kara_config,
+        profile,
+        &config.settings.anchor_key,
+        &config.cached_apps,
+    );
+
+    for binding in &report.bindings {
+        println!(
+            "+ {}  {}",
+            binding.display_key(&config.settings.anchor_key),
+            binding.actions_summary()
+        );
+    }
+    for skipped in &report.skipped {
+        println!("! {}: {}", skipped.description, skipped.reason);
+    }
+
+    let imported = report.bindings.len();
+    let conflicts = merge_imported(&mut config, report.bindings);
+    for key in &conflicts {
+        println!("= {}: already bound in config.toml, kept existing binding", key);
+    }
+
+    let added = imported - conflicts.len();
+    if args.dry_run {
+        println!("Dry run: {} bindings would be imported", added);
+        return Ok(());
+    }
+
+    save_config(&config)?;
+    println!(
+        "Imported {} bindings, {} rules not convertible. Run `rcmdb apply`, then remove the original rules in Karabiner-Elements.",
+        added,
+        report.skipped.len()
+    );
+    Ok(())
+}
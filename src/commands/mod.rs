@@ -0,0 +1,4 @@
+pub mod apply;
+pub mod diff;
+pub mod focus_url;
+pub mod import;
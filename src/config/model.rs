@@ -1,22 +1,20 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-#[serde(rename_all = "lowercase")]
+/// Browser a URL action opens in: a built-in, or a `[[browsers]]` definition by name
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(from = "String", into = "String")]
 pub enum Browser {
+    #[default]
     Firefox,
     Chrome,
     Safari,
     Arc,
     Edge,
-}
-
-impl Default for Browser {
-    fn default() -> Self {
-        Browser::Firefox
-    }
+    Custom(String),
 }
 
 impl Browser {
+    /// The built-in browsers
     pub fn all() -> &'static [Browser] {
         &[
             Browser::Firefox,
@@ -27,75 +25,298 @@ impl Browser {
         ]
     }
 
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             Browser::Firefox => "firefox",
             Browser::Chrome => "chrome",
             Browser::Safari => "safari",
             Browser::Arc => "arc",
             Browser::Edge => "edge",
+            Browser::Custom(name) => name,
         }
     }
 
-    pub fn display_name(&self) -> &'static str {
+    pub fn display_name(&self) -> &str {
         match self {
             Browser::Firefox => "Firefox",
             Browser::Chrome => "Chrome",
             Browser::Safari => "Safari",
             Browser::Arc => "Arc",
             Browser::Edge => "Edge",
+            Browser::Custom(name) => name,
         }
     }
+
+    /// How to drive a built-in browser; None for custom ones
+    pub fn builtin_definition(&self) -> Option<BrowserDefinition> {
+        let support = "~/Library/Application Support/";
+        let (app, family, user_data_dir) = match self {
+            Browser::Firefox => ("Firefox", BrowserFamily::Firefox, None),
+            Browser::Chrome => ("Google Chrome", BrowserFamily::Chromium, Some("Google/Chrome")),
+            Browser::Safari => ("Safari", BrowserFamily::Safari, None),
+            Browser::Arc => ("Arc", BrowserFamily::Chromium, Some("Arc/User Data")),
+            Browser::Edge => ("Microsoft Edge", BrowserFamily::Chromium, Some("Microsoft Edge")),
+            Browser::Custom(_) => return None,
+        };
+        Some(BrowserDefinition {
+            name: self.as_str().to_string(),
+            app: Some(app.to_string()),
+            bundle_id: None,
+            family,
+            user_data_dir: user_data_dir.map(|dir| format!("{}{}", support, dir)),
+        })
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
-#[serde(rename_all = "snake_case")]
+impl From<String> for Browser {
+    fn from(name: String) -> Self {
+        Browser::all()
+            .iter()
+            .find(|b| b.as_str() == name)
+            .cloned()
+            .unwrap_or(Browser::Custom(name))
+    }
+}
+
+impl From<Browser> for String {
+    fn from(browser: Browser) -> Self {
+        browser.as_str().to_string()
+    }
+}
+
+/// How `rcmdb focus-url` finds and selects a browser's tabs
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BrowserFamily {
+    /// Chrome, Brave, Vivaldi, Edge, Arc...: tabs selected by index
+    Chromium,
+    /// Safari and browsers with its scripting dictionary (Orion): tabs selected by reference
+    Safari,
+    /// No tab scripting (Firefox, Zen): the URL is always opened
+    Firefox,
+}
+
+impl BrowserFamily {
+    pub fn all() -> &'static [BrowserFamily] {
+        &[
+            BrowserFamily::Chromium,
+            BrowserFamily::Safari,
+            BrowserFamily::Firefox,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BrowserFamily::Chromium => "chromium",
+            BrowserFamily::Safari => "safari",
+            BrowserFamily::Firefox => "firefox",
+        }
+    }
+}
+
+/// A `[[browsers]]` entry: a browser beyond the built-ins
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BrowserDefinition {
+    /// What URL actions and default_browser call it
+    pub name: String,
+    /// Application name, as for `open -a`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app: Option<String>,
+    /// Bundle ID; used instead of `app` when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundle_id: Option<String>,
+    pub family: BrowserFamily,
+    /// Chromium user data directory, holding the `Local State` file that lists profiles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_data_dir: Option<String>,
+}
+
+impl BrowserDefinition {
+    /// The bundle ID or, failing that, the application name
+    pub fn application(&self) -> Option<&str> {
+        [&self.bundle_id, &self.app]
+            .into_iter()
+            .flatten()
+            .map(|s| s.as_str())
+            .find(|s| !s.trim().is_empty())
+    }
+}
+
+/// Modifier set held while pressing a binding's key. Presets serialize as a string
+/// ("right_command", "hyper", ...), custom sets as a list of Karabiner modifier names.
+#[derive(Debug, Clone, PartialEq, Default)]
 pub enum AnchorKey {
     #[default]
     RightCommand,
     RightOption,
+    RightControl,
+    Fn,
+    /// command+control+option+shift, usually produced by caps_lock
+    Hyper,
+    Custom(Vec<String>),
 }
 
+/// Karabiner modifier names accepted in a custom anchor, with their short display names
+const KARABINER_MODIFIERS: &[(&str, &str)] = &[
+    ("command", "cmd"),
+    ("left_command", "lcmd"),
+    ("right_command", "rcmd"),
+    ("control", "ctl"),
+    ("left_control", "lctl"),
+    ("right_control", "rctl"),
+    ("option", "opt"),
+    ("left_option", "lopt"),
+    ("right_option", "ropt"),
+    ("shift", "shift"),
+    ("left_shift", "lshift"),
+    ("right_shift", "rshift"),
+    ("fn", "fn"),
+];
+
+const HYPER_MODIFIERS: &[&str] = &["command", "control", "option", "shift"];
+
 impl AnchorKey {
+    /// Presets offered in the settings tab
     pub fn all() -> &'static [AnchorKey] {
-        &[AnchorKey::RightCommand, AnchorKey::RightOption]
+        &[
+            AnchorKey::RightCommand,
+            AnchorKey::RightOption,
+            AnchorKey::RightControl,
+            AnchorKey::Fn,
+            AnchorKey::Hyper,
+        ]
     }
 
-    pub fn as_karabiner_modifier(&self) -> &'static str {
+    fn preset_name(&self) -> Option<&'static str> {
         match self {
-            AnchorKey::RightCommand => "right_command",
-            AnchorKey::RightOption => "right_option",
+            AnchorKey::RightCommand => Some("right_command"),
+            AnchorKey::RightOption => Some("right_option"),
+            AnchorKey::RightControl => Some("right_control"),
+            AnchorKey::Fn => Some("fn"),
+            AnchorKey::Hyper => Some("hyper"),
+            AnchorKey::Custom(_) => None,
         }
     }
 
-    pub fn display_prefix(&self) -> &'static str {
+    /// Karabiner `from.modifiers.mandatory` entries
+    pub fn karabiner_modifiers(&self) -> Vec<&str> {
         match self {
-            AnchorKey::RightCommand => "rcmd",
-            AnchorKey::RightOption => "ropt",
+            AnchorKey::RightCommand => vec!["right_command"],
+            AnchorKey::RightOption => vec!["right_option"],
+            AnchorKey::RightControl => vec!["right_control"],
+            AnchorKey::Fn => vec!["fn"],
+            AnchorKey::Hyper => HYPER_MODIFIERS.to_vec(),
+            AnchorKey::Custom(modifiers) => modifiers.iter().map(|m| m.as_str()).collect(),
         }
     }
 
-    pub fn display_name(&self) -> &'static str {
+    pub fn is_hyper(&self) -> bool {
+        matches!(self, AnchorKey::Hyper)
+    }
+
+    /// The physical key that produces this anchor on its own, if there is one
+    /// (a side-specific modifier or fn; Hyper depends on the caps_lock rule)
+    pub fn physical_key(&self) -> Option<&str> {
+        match self.karabiner_modifiers().as_slice() {
+            [key] if key.starts_with("left_") || key.starts_with("right_") || *key == "fn" => {
+                Some(*key)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn display_prefix(&self) -> String {
         match self {
-            AnchorKey::RightCommand => "Right Command",
-            AnchorKey::RightOption => "Right Option",
+            AnchorKey::Hyper => "hyper".to_string(),
+            _ => self
+                .karabiner_modifiers()
+                .iter()
+                .map(|m| {
+                    KARABINER_MODIFIERS
+                        .iter()
+                        .find(|(name, _)| name == m)
+                        .map(|(_, short)| *short)
+                        .unwrap_or(m)
+                })
+                .collect::<Vec<_>>()
+                .join("+"),
+        }
+    }
+
+    pub fn display_name(&self) -> String {
+        match self {
+            AnchorKey::RightCommand => "Right Command".to_string(),
+            AnchorKey::RightOption => "Right Option".to_string(),
+            AnchorKey::RightControl => "Right Control".to_string(),
+            AnchorKey::Fn => "fn".to_string(),
+            AnchorKey::Hyper => "Hyper (cmd+ctl+opt+shift)".to_string(),
+            AnchorKey::Custom(_) => format!("Custom ({})", self.display_prefix()),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+impl serde::Serialize for AnchorKey {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self {
+            AnchorKey::Custom(modifiers) => modifiers.serialize(s),
+            preset => s.serialize_str(preset.preset_name().unwrap_or_default()),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for AnchorKey {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = AnchorKey;
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "preset name or list of modifiers")
+            }
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                AnchorKey::all()
+                    .iter()
+                    .find(|k| k.preset_name() == Some(v))
+                    .cloned()
+                    .ok_or_else(|| {
+                        E::unknown_variant(
+                            v,
+                            &["right_command", "right_option", "right_control", "fn", "hyper"],
+                        )
+                    })
+            }
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut modifiers = Vec::new();
+                while let Some(m) = seq.next_element::<String>()? {
+                    if !KARABINER_MODIFIERS.iter().any(|(name, _)| *name == m) {
+                        return Err(serde::de::Error::custom(format!(
+                            "unknown modifier '{}'",
+                            m
+                        )));
+                    }
+                    modifiers.push(m);
+                }
+                if modifiers.is_empty() {
+                    return Err(serde::de::Error::custom("anchor needs at least one modifier"));
+                }
+                Ok(AnchorKey::Custom(modifiers))
+            }
+        }
+        d.deserialize_any(Visitor)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum UrlMatchType {
     Exact,
+    #[default]
     Domain,
     Path,
     Glob,
-}
-
-impl Default for UrlMatchType {
-    fn default() -> Self {
-        UrlMatchType::Domain
-    }
+    Regex,
 }
 
 impl UrlMatchType {
@@ -105,6 +326,7 @@ impl UrlMatchType {
             UrlMatchType::Domain,
             UrlMatchType::Path,
             UrlMatchType::Glob,
+            UrlMatchType::Regex,
         ]
     }
 
@@ -114,17 +336,62 @@ impl UrlMatchType {
             UrlMatchType::Domain => "domain",
             UrlMatchType::Path => "path",
             UrlMatchType::Glob => "glob",
+            UrlMatchType::Regex => "regex",
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Loosen exact and glob URL matches
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct UrlMatchOptions {
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ignore_query: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ignore_fragment: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ignore_trailing_slash: bool,
+}
+
+impl UrlMatchOptions {
+    /// (label, enabled) in editor order
+    pub fn flags(&self) -> [(&'static str, bool); 3] {
+        [
+            ("query", self.ignore_query),
+            ("fragment", self.ignore_fragment),
+            ("trailing /", self.ignore_trailing_slash),
+        ]
+    }
+
+    pub fn toggle(&mut self, index: usize) {
+        match index {
+            0 => self.ignore_query = !self.ignore_query,
+            1 => self.ignore_fragment = !self.ignore_fragment,
+            _ => self.ignore_trailing_slash = !self.ignore_trailing_slash,
+        }
+    }
+
+    /// "ignore query, fragment", None when nothing is ignored
+    pub fn summary(&self) -> Option<String> {
+        let ignored: Vec<&str> = self
+            .flags()
+            .iter()
+            .filter(|(_, on)| *on)
+            .map(|(label, _)| *label)
+            .collect();
+        Some(format!("ignore {}", ignored.join(", "))).filter(|_| !ignored.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum Action {
     App {
         target: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         bundle_id: Option<String>,
+        /// Hide the app instead when it is already frontmost (needs a bundle ID)
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        toggle: bool,
     },
     Url {
         target: String,
@@ -132,26 +399,82 @@ pub enum Action {
         match_type: UrlMatchType,
         #[serde(skip_serializing_if = "Option::is_none")]
         browser: Option<Browser>,
+        /// Chromium-family profile, by name or directory as listed in Local State
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        profile: Option<String>,
+        #[serde(flatten)]
+        options: UrlMatchOptions,
     },
     Shell {
         command: String,
     },
+    /// Open a file or folder, optionally with a specific app (bundle ID)
+    Open {
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        with_app: Option<String>,
+    },
+    /// Run a macOS Shortcut, optionally passing text as its input
+    Shortcut {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        input: Option<String>,
+    },
+    /// Run an AppleScript given inline (`source`) or as a path (`file`)
+    #[serde(rename = "applescript")]
+    AppleScript {
+        #[serde(flatten)]
+        script: AppleScript,
+    },
+    /// Pause before the next action (only meaningful with mode = "all")
+    Delay {
+        ms: u64,
+    },
+    /// Type a snippet of text
+    Text {
+        text: String,
+    },
+    /// Send a key combination, e.g. cmd+shift+4
+    Keystroke {
+        key_code: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        modifiers: Vec<KeyModifier>,
+    },
 }
 
 impl Action {
     pub fn display_summary(&self) -> String {
         match self {
-            Action::App { target, bundle_id } => {
-                if bundle_id.is_some() {
+            Action::App {
+                target,
+                bundle_id,
+                toggle,
+            } => {
+                let mut summary = if bundle_id.is_some() {
                     format!("{} ✓", target) // Checkmark shows bundle ID present
                 } else {
                     target.clone()
+                };
+                if *toggle {
+                    summary.push_str(" ⇄");
                 }
+                summary
             }
             Action::Url {
-                target, match_type, ..
+                target,
+                match_type,
+                options,
+                profile,
+                ..
             } => {
-                format!("{} ({})", target, match_type.as_str())
+                let summary = match options.summary() {
+                    Some(ignored) => format!("{} ({}; {})", target, match_type.as_str(), ignored),
+                    None => format!("{} ({})", target, match_type.as_str()),
+                };
+                match profile {
+                    Some(profile) => format!("{} @{}", summary, profile),
+                    None => summary,
+                }
             }
             Action::Shell { command } => {
                 let truncated = if command.len() > 30 {
@@ -161,6 +484,32 @@ impl Action {
                 };
                 format!("$ {}", truncated)
             }
+            Action::Open { path, with_app } => match with_app {
+                Some(app) => format!("open: {} (in {})", path, app),
+                None => format!("open: {}", path),
+            },
+            Action::Shortcut { name, input } => match input {
+                Some(input) => format!("shortcut: {} < {}", name, truncate(input, 20)),
+                None => format!("shortcut: {}", name),
+            },
+            Action::AppleScript { script } => match script {
+                AppleScript::Source(source) => {
+                    let first_line = source.lines().next().unwrap_or_default();
+                    let more = if source.trim_end().contains('\n') { " ..." } else { "" };
+                    format!("osascript: {}{}", truncate(first_line, 30), more)
+                }
+                AppleScript::File(path) => format!("osascript: {}", path),
+            },
+            Action::Delay { ms } => format!("wait {}ms", ms),
+            Action::Text { text } => format!("✎ {}", truncate(&text.replace('\n', "⏎"), 30)),
+            Action::Keystroke {
+                key_code,
+                modifiers,
+            } => {
+                let mut keys: Vec<&str> = modifiers.iter().map(|m| m.short_name()).collect();
+                keys.push(key_code);
+                format!("⌨ {}", keys.join("+"))
+            }
         }
     }
 
@@ -169,17 +518,191 @@ impl Action {
             Action::App { .. } => "App",
             Action::Url { .. } => "URL",
             Action::Shell { .. } => "Shell",
+            Action::Open { .. } => "Open",
+            Action::Shortcut { .. } => "Shortcut",
+            Action::AppleScript { .. } => "AppleScript",
+            Action::Delay { .. } => "Delay",
+            Action::Text { .. } => "Text",
+            Action::Keystroke { .. } => "Keystroke",
         }
     }
 }
 
+/// "abc...", cut at `max` characters
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() > max {
+        format!("{}...", s.chars().take(max.saturating_sub(3)).collect::<String>())
+    } else {
+        s.to_string()
+    }
+}
+
+/// Where an AppleScript action's script comes from
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AppleScript {
+    /// Script text, may span several lines
+    Source(String),
+    /// Path to a .scpt / .applescript file, "~/" is the home directory
+    File(String),
+}
+
+/// Modifier sent along with a Keystroke action's key
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyModifier {
+    Command,
+    Shift,
+    Option,
+    Control,
+    Fn,
+}
+
+impl KeyModifier {
+    pub fn all() -> &'static [KeyModifier] {
+        &[
+            KeyModifier::Command,
+            KeyModifier::Shift,
+            KeyModifier::Option,
+            KeyModifier::Control,
+            KeyModifier::Fn,
+        ]
+    }
+
+    pub fn as_karabiner_modifier(&self) -> &'static str {
+        match self {
+            KeyModifier::Command => "command",
+            KeyModifier::Shift => "shift",
+            KeyModifier::Option => "option",
+            KeyModifier::Control => "control",
+            KeyModifier::Fn => "fn",
+        }
+    }
+
+    /// Accepts the generic and the left_/right_ names
+    pub fn from_karabiner_modifier(name: &str) -> Option<Self> {
+        let name = name
+            .strip_prefix("left_")
+            .or_else(|| name.strip_prefix("right_"))
+            .unwrap_or(name);
+        KeyModifier::all()
+            .iter()
+            .find(|m| m.as_karabiner_modifier() == name)
+            .copied()
+    }
+
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            KeyModifier::Command => "cmd",
+            KeyModifier::Shift => "shift",
+            KeyModifier::Option => "opt",
+            KeyModifier::Control => "ctl",
+            KeyModifier::Fn => "fn",
+        }
+    }
+}
+
+/// What a binding with several actions does on each press
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionMode {
+    /// One action per press, in turn
+    #[default]
+    Cycle,
+    /// Every action on each press, in order
+    All,
+}
+
+impl ActionMode {
+    fn is_cycle(&self) -> bool {
+        *self == ActionMode::Cycle
+    }
+}
+
+/// Modifier held together with anchor+key to pick a binding's alternate actions
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ExtraModifier {
+    Shift,
+    Option,
+    Control,
+}
+
+impl ExtraModifier {
+    pub fn as_karabiner_modifier(&self) -> &'static str {
+        match self {
+            ExtraModifier::Shift => "shift",
+            ExtraModifier::Option => "option",
+            ExtraModifier::Control => "control",
+        }
+    }
+
+    pub fn from_karabiner_modifier(name: &str) -> Option<Self> {
+        match name {
+            "shift" => Some(ExtraModifier::Shift),
+            "option" => Some(ExtraModifier::Option),
+            "control" => Some(ExtraModifier::Control),
+            _ => None,
+        }
+    }
+}
+
+/// Alternate actions fired by anchor+modifiers+key
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModifiedActions {
+    pub modifiers: Vec<ExtraModifier>,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+}
+
+impl ModifiedActions {
+    /// "shift", "shift+option", ...
+    pub fn modifiers_label(&self) -> String {
+        self.modifiers
+            .iter()
+            .map(|m| m.as_karabiner_modifier())
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Binding {
     pub key: String,
+    /// Second stroke of a leader sequence: anchor+key, then this key on its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<String>,
     #[serde(default)]
     pub description: String,
     #[serde(default)]
     pub actions: Vec<Action>,
+    /// Only trigger while one of these apps is frontmost (bundle IDs, or /regex/)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub only_in: Vec<String>,
+    /// Never trigger while one of these apps is frontmost (bundle IDs, or /regex/)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub except_in: Vec<String>,
+    /// Cycle through the actions (default) or run them all at once
+    #[serde(default, skip_serializing_if = "ActionMode::is_cycle")]
+    pub mode: ActionMode,
+    /// Cycle from whichever of the cycle's apps is frontmost instead of the last position
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub focus_aware: bool,
+    /// Don't generate anchor+shift+key to step the cycle backwards
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_reverse_cycle: bool,
+    /// Restart the cycle from the first action after this many ms (None = settings default, 0 = never)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cycle_reset_ms: Option<u64>,
+    /// Alternate actions for anchor+shift/option/control+key
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modified: Vec<ModifiedActions>,
+    /// Run all of these instead when anchor+key is held down; the actions then run on tap
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hold_actions: Vec<Action>,
+    /// Run all of these instead when anchor+key is pressed twice in quick succession
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub double_tap_actions: Vec<Action>,
 }
 
 impl Binding {
@@ -190,16 +713,85 @@ impl Binding {
         if self.actions.len() == 1 {
             return self.actions[0].display_summary();
         }
-        // Cycling: show as A -> B -> C
+        // Cycling: show as A -> B -> C, all at once as A + B + C
+        let separator = match self.mode {
+            ActionMode::Cycle => " -> ",
+            ActionMode::All => " + ",
+        };
         self.actions
             .iter()
             .map(|a| a.display_summary())
             .collect::<Vec<_>>()
-            .join(" -> ")
+            .join(separator)
     }
 
     pub fn display_key(&self, anchor_key: &AnchorKey) -> String {
-        format!("{}+{}", anchor_key.display_prefix(), self.key)
+        match &self.sequence {
+            Some(second) => format!("{}+{} {}", anchor_key.display_prefix(), self.key, second),
+            None => format!("{}+{}", anchor_key.display_prefix(), self.key),
+        }
+    }
+
+    pub fn is_sequence(&self) -> bool {
+        self.sequence.is_some()
+    }
+
+    /// Short description of the modified actions, e.g. "+shift: Slack (domain)"
+    pub fn modified_summary(&self) -> Option<String> {
+        if self.modified.is_empty() {
+            return None;
+        }
+        let parts: Vec<String> = self
+            .modified
+            .iter()
+            .map(|m| {
+                let actions: Vec<String> = m.actions.iter().map(|a| a.display_summary()).collect();
+                format!("+{}: {}", m.modifiers_label(), actions.join(" -> "))
+            })
+            .collect();
+        Some(parts.join("; "))
+    }
+
+    pub fn hold_summary(&self) -> Option<String> {
+        if self.hold_actions.is_empty() {
+            return None;
+        }
+        let actions: Vec<String> = self.hold_actions.iter().map(|a| a.display_summary()).collect();
+        Some(format!("hold: {}", actions.join(" + ")))
+    }
+
+    pub fn double_tap_summary(&self) -> Option<String> {
+        if self.double_tap_actions.is_empty() {
+            return None;
+        }
+        let actions: Vec<String> =
+            self.double_tap_actions.iter().map(|a| a.display_summary()).collect();
+        Some(format!("2×: {}", actions.join(" + ")))
+    }
+
+    /// Effective cycle reset timeout, None when the cycle never resets
+    pub fn cycle_reset(&self, settings: &Settings) -> Option<u64> {
+        Some(self.cycle_reset_ms.unwrap_or(settings.cycle_reset_ms)).filter(|ms| *ms > 0)
+    }
+
+    pub fn has_app_conditions(&self) -> bool {
+        !self.only_in.is_empty() || !self.except_in.is_empty()
+    }
+
+    /// Short description of the app conditions, e.g. "only com.apple.Safari"
+    pub fn conditions_summary(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if !self.only_in.is_empty() {
+            parts.push(format!("only {}", self.only_in.join(", ")));
+        }
+        if !self.except_in.is_empty() {
+            parts.push(format!("except {}", self.except_in.join(", ")));
+        }
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join("; "))
+        }
     }
 }
 
@@ -282,6 +874,68 @@ pub struct Settings {
     pub default_browser: Browser,
     #[serde(default, skip_serializing_if = "CenterMouseMode::is_off")]
     pub center_mouse: CenterMouseMode,
+    /// Karabiner profiles to write rules into (empty = first profile)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<String>,
+    /// Generate an [rcmdb] rule turning caps_lock into Hyper (only with the Hyper anchor)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub caps_lock_hyper: bool,
+    /// Run when the anchor key is tapped on its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor_tap_action: Option<Action>,
+    /// Default cycle reset for bindings without their own cycle_reset_ms (0 = never)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub cycle_reset_ms: u64,
+    /// How long a leader key waits for the second stroke
+    #[serde(
+        default = "default_leader_timeout_ms",
+        skip_serializing_if = "is_default_leader_timeout"
+    )]
+    pub leader_timeout_ms: u64,
+    /// How long anchor+key must be held to run a binding's hold_actions
+    #[serde(
+        default = "default_hold_threshold_ms",
+        skip_serializing_if = "is_default_hold_threshold"
+    )]
+    pub hold_threshold_ms: u64,
+    /// How soon the second press must follow the first to run double_tap_actions
+    #[serde(
+        default = "default_double_tap_timeout_ms",
+        skip_serializing_if = "is_default_double_tap_timeout"
+    )]
+    pub double_tap_timeout_ms: u64,
+}
+
+const DEFAULT_LEADER_TIMEOUT_MS: u64 = 1000;
+const DEFAULT_HOLD_THRESHOLD_MS: u64 = 500;
+const DEFAULT_DOUBLE_TAP_TIMEOUT_MS: u64 = 300;
+
+fn default_leader_timeout_ms() -> u64 {
+    DEFAULT_LEADER_TIMEOUT_MS
+}
+
+fn is_default_leader_timeout(ms: &u64) -> bool {
+    *ms == DEFAULT_LEADER_TIMEOUT_MS
+}
+
+fn default_hold_threshold_ms() -> u64 {
+    DEFAULT_HOLD_THRESHOLD_MS
+}
+
+fn is_default_hold_threshold(ms: &u64) -> bool {
+    *ms == DEFAULT_HOLD_THRESHOLD_MS
+}
+
+fn default_double_tap_timeout_ms() -> u64 {
+    DEFAULT_DOUBLE_TAP_TIMEOUT_MS
+}
+
+fn is_default_double_tap_timeout(ms: &u64) -> bool {
+    *ms == DEFAULT_DOUBLE_TAP_TIMEOUT_MS
+}
+
+fn is_zero(ms: &u64) -> bool {
+    *ms == 0
 }
 
 impl Default for Settings {
@@ -290,27 +944,56 @@ impl Default for Settings {
             anchor_key: AnchorKey::default(),
             default_browser: Browser::Firefox,
             center_mouse: CenterMouseMode::Off,
+            profiles: Vec::new(),
+            caps_lock_hyper: false,
+            anchor_tap_action: None,
+            cycle_reset_ms: 0,
+            leader_timeout_ms: DEFAULT_LEADER_TIMEOUT_MS,
+            hold_threshold_ms: DEFAULT_HOLD_THRESHOLD_MS,
+            double_tap_timeout_ms: DEFAULT_DOUBLE_TAP_TIMEOUT_MS,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Settings {
+    /// Key whose lone tap runs anchor_tap_action: the anchor's own key, or
+    /// caps_lock when it produces Hyper
+    pub fn anchor_tap_key(&self) -> Option<&str> {
+        if self.anchor_key.is_hyper() && self.caps_lock_hyper {
+            return Some("caps_lock");
+        }
+        self.anchor_key.physical_key()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
     #[serde(default)]
     pub settings: Settings,
+    /// Browsers beyond the built-ins
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub browsers: Vec<BrowserDefinition>,
     #[serde(default)]
     pub bindings: Vec<Binding>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub cached_apps: Vec<crate::app_discovery::DiscoveredApp>,
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            settings: Settings::default(),
-            bindings: Vec::new(),
-            cached_apps: Vec::new(),
-        }
+impl Config {
+    /// Browsers to choose from: the built-ins, then `[[browsers]]`
+    pub fn browser_choices(&self) -> Vec<Browser> {
+        let custom = self.browsers.iter().map(|b| Browser::from(b.name.clone()));
+        Browser::all().iter().cloned().chain(custom).collect()
+    }
+
+    /// How to drive `browser`; None for a name without a `[[browsers]]` entry
+    pub fn browser_definition(&self, browser: &Browser) -> Option<BrowserDefinition> {
+        browser.builtin_definition().or_else(|| {
+            self.browsers
+                .iter()
+                .find(|b| b.name == browser.as_str())
+                .cloned()
+        })
     }
 }
 
@@ -349,6 +1032,120 @@ mod tests {
         assert!(!toml.contains("center_mouse"), "Off should be omitted from serialized output");
     }
 
+    #[test]
+    fn test_anchor_key_serde() {
+        let legacy: Settings = toml::from_str("anchor_key = \"right_option\"\n").unwrap();
+        assert_eq!(legacy.anchor_key, AnchorKey::RightOption);
+
+        let hyper: Settings = toml::from_str("anchor_key = \"hyper\"\n").unwrap();
+        assert_eq!(hyper.anchor_key.display_prefix(), "hyper");
+        assert_eq!(hyper.anchor_key.karabiner_modifiers().len(), 4);
+
+        let custom: Settings =
+            toml::from_str("anchor_key = [\"left_command\", \"left_option\"]\n").unwrap();
+        assert_eq!(custom.anchor_key.display_prefix(), "lcmd+lopt");
+        assert!(toml::to_string(&custom)
+            .unwrap()
+            .contains("anchor_key = [\"left_command\", \"left_option\"]"));
+
+        assert_eq!(AnchorKey::RightControl.display_prefix(), "rctl");
+        assert!(toml::to_string(&Settings::default())
+            .unwrap()
+            .contains("anchor_key = \"right_command\""));
+        assert!(toml::from_str::<Settings>("anchor_key = [\"super\"]\n").is_err());
+    }
+
+    #[test]
+    fn test_applescript_action_serde() {
+        let binding: Binding = toml::from_str(
+            "key = \"a\"\n\
+             [[actions]]\ntype = \"applescript\"\nsource = \"beep\\nbeep\"\n\
+             [[actions]]\ntype = \"applescript\"\nfile = \"~/Scripts/x.scpt\"\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            &binding.actions[0],
+            Action::AppleScript { script: AppleScript::Source(s) } if s == "beep\nbeep"
+        ));
+        assert_eq!(binding.actions[0].display_summary(), "osascript: beep ...");
+        assert!(matches!(
+            &binding.actions[1],
+            Action::AppleScript { script: AppleScript::File(f) } if f == "~/Scripts/x.scpt"
+        ));
+
+        let toml = toml::to_string(&binding).unwrap();
+        assert!(toml.contains("file = \"~/Scripts/x.scpt\""), "{}", toml);
+        assert!(toml::from_str::<Action>("type = \"applescript\"\n").is_err());
+    }
+
+    #[test]
+    fn test_anchor_tap_key() {
+        let settings: Settings = toml::from_str(
+            "anchor_tap_action = { type = \"keystroke\", key_code = \"spacebar\", \
+             modifiers = [\"command\"] }\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            &settings.anchor_tap_action,
+            Some(Action::Keystroke { key_code, .. }) if key_code == "spacebar"
+        ));
+        assert_eq!(settings.anchor_tap_key(), Some("right_command"));
+
+        let hyper = Settings {
+            anchor_key: AnchorKey::Hyper,
+            ..settings
+        };
+        assert_eq!(hyper.anchor_tap_key(), None);
+        let caps = Settings {
+            caps_lock_hyper: true,
+            ..hyper
+        };
+        assert_eq!(caps.anchor_tap_key(), Some("caps_lock"));
+        assert_eq!(AnchorKey::Fn.physical_key(), Some("fn"));
+        assert_eq!(AnchorKey::Custom(vec!["command".to_string()]).physical_key(), None);
+    }
+
+    #[test]
+    fn test_custom_browsers() {
+        let config: Config = toml::from_str(
+            "[settings]\ndefault_browser = \"brave\"\n\
+             [[browsers]]\nname = \"brave\"\napp = \"Brave Browser\"\nfamily = \"chromium\"\n\
+             [[browsers]]\nname = \"orion\"\nbundle_id = \"com.kagi.kagimacOS\"\n\
+             app = \"Orion\"\nfamily = \"safari\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.settings.default_browser, Browser::Custom("brave".to_string()));
+        assert_eq!(
+            config.browser_choices().last(),
+            Some(&Browser::Custom("orion".to_string()))
+        );
+        assert_eq!(config.browser_choices().len(), Browser::all().len() + 2);
+
+        let orion = config
+            .browser_definition(&Browser::Custom("orion".to_string()))
+            .unwrap();
+        assert_eq!(orion.family, BrowserFamily::Safari);
+        assert_eq!(orion.application(), Some("com.kagi.kagimacOS"));
+        assert_eq!(
+            config.browser_definition(&Browser::Chrome).unwrap().application(),
+            Some("Google Chrome")
+        );
+        assert!(config
+            .browser_definition(&Browser::Custom("zen".to_string()))
+            .is_none());
+
+        // Built-in names still read as built-ins
+        let toml = toml::to_string(&config).unwrap();
+        assert!(toml.contains("default_browser = \"brave\""), "{}", toml);
+        assert_eq!(Browser::from("safari".to_string()), Browser::Safari);
+
+        let gmail: Action = toml::from_str(
+            "type = \"url\"\ntarget = \"mail.google.com\"\nbrowser = \"chrome\"\nprofile = \"Work\"\n",
+        )
+        .unwrap();
+        assert_eq!(gmail.display_summary(), "mail.google.com (domain) @Work");
+    }
+
     #[test]
     fn test_settings_deserialize_legacy_center_mouse_true() {
         let toml = "center_mouse = true\n";
@@ -1,6 +1,6 @@
 use anyhow::{Context, Result};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use super::model::Config;
 
@@ -22,11 +22,14 @@ pub fn scripts_dir() -> Result<PathBuf> {
 }
 
 pub fn load_config() -> Result<Config> {
-    let path = config_path()?;
+    load_config_from(&config_path()?)
+}
+
+pub fn load_config_from(path: &Path) -> Result<Config> {
     if !path.exists() {
         return Ok(Config::default());
     }
-    let content = fs::read_to_string(&path)
+    let content = fs::read_to_string(path)
         .with_context(|| format!("Failed to read config from {:?}", path))?;
     let mut config: Config = toml::from_str(&content).with_context(|| "Failed to parse config TOML")?;
 
@@ -51,3 +54,29 @@ pub fn ensure_scripts_dir() -> Result<PathBuf> {
     fs::create_dir_all(&dir).with_context(|| format!("Failed to create scripts dir {:?}", dir))?;
     Ok(dir)
 }
+
+/// Resolve a leading "~" or "$HOME" against the current home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    let rest = path.strip_prefix('~').or_else(|| path.strip_prefix("$HOME"));
+    match (rest, dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_home() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_home("~"), home);
+        assert_eq!(expand_home("~/work"), home.join("work"));
+        assert_eq!(expand_home("$HOME/a b"), home.join("a b"));
+        assert_eq!(expand_home("~work"), PathBuf::from("~work"));
+        assert_eq!(expand_home("/tmp"), PathBuf::from("/tmp"));
+    }
+}
@@ -0,0 +1,259 @@
+use serde_json::Value;
+use similar::TextDiff;
+
+use crate::karabiner::generator::{is_rcmdb_rule, profile_names};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleChange {
+    Added,
+    Removed,
+    Changed(Vec<ManipulatorChange>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ManipulatorChange {
+    Added(usize),
+    Removed(usize),
+    Changed(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleDiff {
+    pub profile: String,
+    pub description: String,
+    pub change: RuleChange,
+}
+
+/// Collect [rcmdb] rules from one profile of a karabiner.json document
+fn rcmdb_rules(doc: &Value, profile: usize) -> Vec<&Value> {
+    doc.get("profiles")
+        .and_then(|p| p.get(profile))
+        .and_then(|p| p.get("complex_modifications"))
+        .and_then(|c| c.get("rules"))
+        .and_then(|r| r.as_array())
+        .map(|rules| rules.iter().filter(|r| is_rcmdb_rule(r)).collect())
+        .unwrap_or_default()
+}
+
+fn rule_description(rule: &Value) -> &str {
+    rule.get("description").and_then(|d| d.as_str()).unwrap_or("")
+}
+
+/// Rule identity: the "[rcmdb] rcmd+t" head, so editing a binding's description
+/// shows up as a change rather than a remove + add
+fn rule_id(rule: &Value) -> &str {
+    let description = rule_description(rule);
+    description
+        .split_once(": ")
+        .map(|(head, _)| head)
+        .unwrap_or(description)
+}
+
+fn manipulators(rule: &Value) -> &[Value] {
+    rule.get("manipulators")
+        .and_then(|m| m.as_array())
+        .map(|m| m.as_slice())
+        .unwrap_or(&[])
+}
+
+/// Structural diff of the [rcmdb] rules between two karabiner.json documents, profile by profile
+pub fn diff_rules(old: &Value, new: &Value) -> Vec<RuleDiff> {
+    profile_names(new)
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, name)| {
+            diff_profile_rules(name, &rcmdb_rules(old, idx), &rcmdb_rules(new, idx))
+        })
+        .collect()
+}
+
+fn diff_profile_rules(profile: &str, old_rules: &[&Value], new_rules: &[&Value]) -> Vec<RuleDiff> {
+    let mut matched = vec![false; old_rules.len()];
+    let mut diffs = Vec::new();
+
+    for new_rule in new_rules {
+        let old_idx = old_rules
+            .iter()
+            .enumerate()
+            .position(|(i, r)| !matched[i] && rule_id(r) == rule_id(new_rule));
+
+        let Some(old_idx) = old_idx else {
+            diffs.push(RuleDiff {
+                profile: profile.to_string(),
+                description: rule_description(new_rule).to_string(),
+                change: RuleChange::Added,
+            });
+            continue;
+        };
+        matched[old_idx] = true;
+
+        let old_rule = old_rules[old_idx];
+        if old_rule == *new_rule {
+            continue;
+        }
+
+        diffs.push(RuleDiff {
+            profile: profile.to_string(),
+            description: rule_description(new_rule).to_string(),
+            change: RuleChange::Changed(diff_manipulators(
+                manipulators(old_rule),
+                manipulators(new_rule),
+            )),
+        });
+    }
+
+    for (old_rule, _) in old_rules.iter().zip(&matched).filter(|(_, m)| !**m) {
+        diffs.push(RuleDiff {
+            profile: profile.to_string(),
+            description: rule_description(old_rule).to_string(),
+            change: RuleChange::Removed,
+        });
+    }
+
+    diffs
+}
+
+/// Positional manipulator diff (manipulator order is significant to Karabiner)
+fn diff_manipulators(old: &[Value], new: &[Value]) -> Vec<ManipulatorChange> {
+    (0..old.len().max(new.len()))
+        .filter_map(|i| match (old.get(i), new.get(i)) {
+            (Some(o), Some(n)) if o != n => Some(ManipulatorChange::Changed(i)),
+            (None, Some(_)) => Some(ManipulatorChange::Added(i)),
+            (Some(_), None) => Some(ManipulatorChange::Removed(i)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Human-readable summary of a structural diff, grouped by profile
+pub fn format_summary(diffs: &[RuleDiff]) -> String {
+    let mut out = String::new();
+    let mut current_profile = None;
+    for diff in diffs {
+        if current_profile != Some(&diff.profile) {
+            out.push_str(&format!("Profile \"{}\":\n", diff.profile));
+            current_profile = Some(&diff.profile);
+        }
+        match &diff.change {
+            RuleChange::Added => out.push_str(&format!("+ {}\n", diff.description)),
+            RuleChange::Removed => out.push_str(&format!("- {}\n", diff.description)),
+            RuleChange::Changed(changes) => {
+                out.push_str(&format!("~ {}\n", diff.description));
+                for change in changes {
+                    let line = match change {
+                        ManipulatorChange::Added(i) => format!("    + manipulator {}", i),
+                        ManipulatorChange::Removed(i) => format!("    - manipulator {}", i),
+                        ManipulatorChange::Changed(i) => format!("    ~ manipulator {}", i),
+                    };
+                    out.push_str(&line);
+                    out.push('\n');
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Unified text diff of the two documents, both pretty-printed the way apply writes them
+pub fn unified_diff(old: &Value, new: &Value) -> String {
+    let old_text = serde_json::to_string_pretty(old).unwrap_or_default() + "\n";
+    let new_text = serde_json::to_string_pretty(new).unwrap_or_default() + "\n";
+    TextDiff::from_lines(&old_text, &new_text)
+        .unified_diff()
+        .context_radius(3)
+        .header("karabiner.json", "karabiner.json (rcmdb)")
+        .to_string()
+}
+
+/// RFC 6902 JSON patch that turns `old` into `new`
+pub fn json_patch(old: &Value, new: &Value) -> Value {
+    serde_json::to_value(json_patch::diff(old, new)).unwrap_or(Value::Array(Vec::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn doc(rules: Value) -> Value {
+        json!({ "profiles": [{ "name": "Default", "complex_modifications": { "rules": rules } }] })
+    }
+
+    fn rule(description: &str, manipulators: Value) -> Value {
+        json!({ "description": description, "manipulators": manipulators })
+    }
+
+    #[test]
+    fn test_diff_rules_added_removed_changed() {
+        let old = doc(json!([
+            rule("[rcmdb] rcmd+a: A", json!([{ "to": 1 }])),
+            rule("[rcmdb] rcmd+b: B", json!([{ "to": 1 }, { "to": 2 }])),
+            rule("[rcmdb] rcmd+c", json!([])),
+            rule("Hand-written", json!([]))
+        ]));
+        let new = doc(json!([
+            rule("Hand-written", json!([{ "ignored": true }])),
+            rule("[rcmdb] rcmd+a: A", json!([{ "to": 1 }])),
+            rule("[rcmdb] rcmd+b: Renamed", json!([{ "to": 9 }])),
+            rule("[rcmdb] rcmd+d", json!([]))
+        ]));
+
+        let diffs = diff_rules(&old, &new);
+        assert_eq!(
+            diffs,
+            vec![
+                RuleDiff {
+                    profile: "Default".to_string(),
+                    description: "[rcmdb] rcmd+b: Renamed".to_string(),
+                    change: RuleChange::Changed(vec![
+                        ManipulatorChange::Changed(0),
+                        ManipulatorChange::Removed(1),
+                    ]),
+                },
+                RuleDiff {
+                    profile: "Default".to_string(),
+                    description: "[rcmdb] rcmd+d".to_string(),
+                    change: RuleChange::Added,
+                },
+                RuleDiff {
+                    profile: "Default".to_string(),
+                    description: "[rcmdb] rcmd+c".to_string(),
+                    change: RuleChange::Removed,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_rules_per_profile() {
+        let old = json!({ "profiles": [
+            { "name": "Work", "complex_modifications": { "rules": [] } },
+            { "name": "Gaming", "complex_modifications": { "rules": [] } }
+        ] });
+        let mut new = old.clone();
+        new["profiles"][1]["complex_modifications"]["rules"] =
+            json!([rule("[rcmdb] rcmd+a", json!([]))]);
+
+        let diffs = diff_rules(&old, &new);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].profile, "Gaming");
+        assert_eq!(format_summary(&diffs), "Profile \"Gaming\":\n+ [rcmdb] rcmd+a\n");
+    }
+
+    #[test]
+    fn test_no_changes_produces_empty_outputs() {
+        let d = doc(json!([rule("[rcmdb] rcmd+a", json!([{ "to": 1 }]))]));
+        assert!(diff_rules(&d, &d).is_empty());
+        assert!(unified_diff(&d, &d).is_empty());
+        assert_eq!(json_patch(&d, &d), json!([]));
+    }
+
+    #[test]
+    fn test_json_patch_targets_rules() {
+        let old = doc(json!([]));
+        let new = doc(json!([rule("[rcmdb] rcmd+a", json!([]))]));
+        let patch = json_patch(&old, &new);
+        assert_eq!(patch[0]["op"], "add");
+        assert_eq!(patch[0]["path"], "/profiles/0/complex_modifications/rules/0");
+    }
+}
@@ -1,139 +1,711 @@
 use anyhow::{Context, Result};
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::fs;
+use std::path::Path;
 
-use crate::config::{Action, AnchorKey, Binding, Browser, CenterMouseMode, Config};
+use crate::config::{
+    Action, ActionMode, AnchorKey, AppleScript, Binding, Browser, CenterMouseMode, Config, ExtraModifier,
+    Settings,
+};
 use crate::karabiner::backup::{create_backup, karabiner_config_path};
+use crate::karabiner::quote::{applescript_string, shell_path, shell_quote};
+use crate::keycodes::char_to_key;
 
 const RULE_PREFIX: &str = "[rcmdb]";
-const VAR_PREFIX: &str = "rcmdb_";
+pub const VAR_PREFIX: &str = "rcmdb_";
 // ^ Runtime path: $HOME expands when karabiner executes the shell command, not at save time.
 const SCRIPTS_RUNTIME_DIR: &str = "$HOME/.config/karabiner-rcmd-binder/scripts";
 
 /// Generate Karabiner complex_modifications rules from our config
 pub fn generate_rules(config: &Config) -> Vec<Value> {
-    config
-        .bindings
-        .iter()
-        .map(|b| generate_binding_rule(b, &config.settings.anchor_key, &config.settings.default_browser, config.settings.center_mouse))
-        .collect()
+    // Karabiner uses the first matching manipulator, so app-specific bindings
+    // must come before an unconditional binding on the same key (stable sort)
+    let mut ordered: Vec<&Binding> = config.bindings.iter().collect();
+    ordered.sort_by_key(|b| !b.has_app_conditions());
+    let (sequences, plain): (Vec<&Binding>, Vec<&Binding>) =
+        ordered.into_iter().partition(|b| b.is_sequence());
+
+    // Leader rules go first: their second-stroke manipulators only match while a
+    // leader is active, and must win over anchor+key bindings if the anchor is still held
+    let mut leaders: Vec<&str> = Vec::new();
+    for b in &sequences {
+        if !leaders.contains(&b.key.as_str()) {
+            leaders.push(&b.key);
+        }
+    }
+
+    let mut key_counts: HashMap<String, usize> = HashMap::new();
+    let mut next_var_base = |key: String| {
+        let slot = key_counts.entry(key.clone()).or_insert(0);
+        let var_base = binding_var_base(&key, *slot);
+        *slot += 1;
+        var_base
+    };
+
+    let mut rules: Vec<Value> = leaders
+        .into_iter()
+        .map(|leader| {
+            let steps: Vec<(&Binding, String)> = sequences
+                .iter()
+                .filter(|b| b.key == leader)
+                .map(|b| {
+                    let second = b.sequence.as_deref().unwrap_or_default();
+                    (*b, next_var_base(format!("{}_{}", leader, second)))
+                })
+                .collect();
+            generate_leader_rule(leader, &steps, &config.settings)
+        })
+        .collect();
+
+    rules.extend(plain.into_iter().map(|b| {
+        let var_base = next_var_base(b.key.clone());
+        generate_binding_rule(b, &var_base, &config.settings)
+    }));
+
+    let settings = &config.settings;
+    let tap = settings
+        .anchor_tap_action
+        .as_ref()
+        .map(|a| action_to_karabiner(a, &settings.default_browser, settings.center_mouse));
+    if settings.anchor_key.is_hyper() && settings.caps_lock_hyper {
+        rules.insert(0, caps_lock_hyper_rule(tap));
+    } else if let (Some(key), Some(tap)) = (settings.anchor_key.physical_key(), tap) {
+        rules.insert(0, anchor_tap_rule(key, &settings.anchor_key, tap));
+    }
+    rules
 }
 
-/// Generate a single rule for a binding
-fn generate_binding_rule(binding: &Binding, anchor_key: &AnchorKey, default_browser: &Browser, center_mouse: CenterMouseMode) -> Value {
-    let description = if binding.description.is_empty() {
-        format!("{} {}+{}", RULE_PREFIX, anchor_key.display_prefix(), binding.key)
+/// caps_lock held = left_shift with command+control+option, i.e. Hyper.
+/// Tapped alone it runs the anchor tap action, if any.
+fn caps_lock_hyper_rule(tap: Option<Vec<Value>>) -> Value {
+    let mut manipulator = json!({
+        "type": "basic",
+        "from": {
+            "key_code": "caps_lock",
+            "modifiers": { "optional": ["any"] }
+        },
+        "to": [{
+            "key_code": "left_shift",
+            "modifiers": ["left_command", "left_control", "left_option"]
+        }]
+    });
+    if let Some(tap) = tap {
+        manipulator["to_if_alone"] = json!(tap);
+    }
+    json!({
+        "description": format!("{} caps_lock → hyper", RULE_PREFIX),
+        "manipulators": [manipulator]
+    })
+}
+
+/// The anchor key passes through as itself, so anchor+key bindings keep working,
+/// and runs the anchor tap action when pressed and released on its own
+fn anchor_tap_rule(key: &str, anchor_key: &AnchorKey, tap: Vec<Value>) -> Value {
+    json!({
+        "description": format!("{} {} tap", RULE_PREFIX, anchor_key.display_prefix()),
+        "manipulators": [{
+            "type": "basic",
+            "from": {
+                "key_code": key,
+                "modifiers": { "optional": ["any"] }
+            },
+            "to": [{ "key_code": key }],
+            "to_if_alone": tap
+        }]
+    })
+}
+
+/// Prefix for a binding's Karabiner variables: "rcmdb_t" for the first binding on a key,
+/// "rcmdb_t_2", "rcmdb_t_3", ... for further app-specific bindings on the same key
+pub fn binding_var_base(key: &str, slot: usize) -> String {
+    if slot == 0 {
+        format!("{}{}", VAR_PREFIX, key)
     } else {
-        format!(
-            "{} {}+{}: {}",
-            RULE_PREFIX, anchor_key.display_prefix(), binding.key, binding.description
-        )
-    };
+        format!("{}{}_{}", VAR_PREFIX, key, slot + 1)
+    }
+}
+
+/// Variable set while a leader key waits for its second stroke
+pub fn leader_var(key: &str) -> String {
+    format!("{}leader_{}", VAR_PREFIX, key)
+}
 
-    let manipulators = if binding.actions.len() <= 1 {
-        // Single action - no cycling needed
-        generate_single_action_manipulators(binding, anchor_key, default_browser, center_mouse)
+/// Generate a single rule for a binding
+fn generate_binding_rule(binding: &Binding, var_base: &str, settings: &Settings) -> Value {
+    let anchor_key = &settings.anchor_key;
+    let mut head = format!("{} {}+{}", RULE_PREFIX, anchor_key.display_prefix(), binding.key);
+    if let Some(conditions) = binding.conditions_summary() {
+        head.push_str(&format!(" ({})", conditions));
+    }
+    let description = if binding.description.is_empty() {
+        head
     } else {
-        // Multiple actions - cycling
-        generate_cycling_manipulators(binding, anchor_key, default_browser, center_mouse)
+        format!("{}: {}", head, binding.description)
     };
 
     json!({
         "description": description,
+        "manipulators": binding_manipulators(binding, var_base, settings, |extra| {
+            from_event(&binding.key, anchor_key, extra)
+        })
+    })
+}
+
+/// Modifier keys pass through an armed leader, so shift+key etc. can be second strokes
+const MODIFIER_KEY_CODES: &[&str] = &[
+    "left_shift",
+    "right_shift",
+    "left_control",
+    "right_control",
+    "left_option",
+    "right_option",
+    "left_command",
+    "right_command",
+    "fn",
+];
+
+/// Generate the rule for a leader key: anchor+key arms the leader variable, each
+/// sequence binding fires on its plain second key, and any other key disarms it.
+/// Modifier keys leave it armed. The delayed action disarms it when no second key
+/// arrives in time.
+fn generate_leader_rule(leader: &str, steps: &[(&Binding, String)], settings: &Settings) -> Value {
+    let var = leader_var(leader);
+    let armed = json!({ "type": "variable_if", "name": var, "value": 1 });
+    let disarm = json!({ "set_variable": { "name": var, "value": 0 } });
+
+    let mut manipulators = vec![json!({
+        "type": "basic",
+        "from": from_event(leader, &settings.anchor_key, &[]),
+        "to": [{ "set_variable": { "name": var, "value": 1 } }],
+        "to_delayed_action": { "to_if_invoked": [disarm.clone()] },
+        "parameters": {
+            "basic.to_delayed_action_delay_milliseconds": settings.leader_timeout_ms
+        }
+    })];
+
+    for (binding, var_base) in steps {
+        let second = binding.sequence.as_deref().unwrap_or_default();
+        let second_stroke = |extra: &[ExtraModifier]| {
+            let mut from = json!({
+                "key_code": second,
+                "modifiers": { "optional": ["any"] }
+            });
+            if !extra.is_empty() {
+                from["modifiers"]["mandatory"] =
+                    json!(extra.iter().map(|m| m.as_karabiner_modifier()).collect::<Vec<_>>());
+            }
+            from
+        };
+        for mut manipulator in binding_manipulators(binding, var_base, settings, second_stroke) {
+            if let Some(to) = manipulator["to"].as_array_mut() {
+                to.push(disarm.clone());
+            }
+            let mut conditions = vec![armed.clone()];
+            if let Some(existing) = manipulator["conditions"].as_array() {
+                conditions.extend(existing.iter().cloned());
+            }
+            manipulator["conditions"] = json!(conditions);
+            manipulators.push(manipulator);
+        }
+    }
+
+    manipulators.extend(MODIFIER_KEY_CODES.iter().map(|key| {
+        json!({
+            "type": "basic",
+            "from": { "key_code": key, "modifiers": { "optional": ["any"] } },
+            "to": [{ "key_code": key }],
+            "conditions": [armed.clone()]
+        })
+    }));
+    manipulators.push(json!({
+        "type": "basic",
+        "from": { "any": "key_code", "modifiers": { "optional": ["any"] } },
+        "to": [disarm],
+        "conditions": [armed]
+    }));
+
+    let seconds: Vec<String> = steps
+        .iter()
+        .map(|(b, _)| {
+            let second = b.sequence.clone().unwrap_or_default();
+            match b.conditions_summary() {
+                Some(conditions) => format!("{} ({})", second, conditions),
+                None => second,
+            }
+        })
+        .collect();
+    json!({
+        "description": format!(
+            "{} {}+{} (leader): {}",
+            RULE_PREFIX,
+            settings.anchor_key.display_prefix(),
+            leader,
+            seconds.join(", ")
+        ),
         "manipulators": manipulators
     })
 }
 
-/// Generate manipulators for a single action (no cycling)
-fn generate_single_action_manipulators(
+/// Manipulators for a binding: one per action when cycling. Modified actions and the
+/// reverse cycle come first so Karabiner matches anchor+shift+key before the plain
+/// anchor+key. Hold and double-tap actions only apply to the plain anchor+key.
+/// `from` builds the from event for a set of extra modifiers.
+fn binding_manipulators(
     binding: &Binding,
-    anchor_key: &AnchorKey,
-    default_browser: &Browser,
-    center_mouse: CenterMouseMode,
+    var_base: &str,
+    settings: &Settings,
+    from: impl Fn(&[ExtraModifier]) -> Value,
 ) -> Vec<Value> {
-    let from = json!({
-        "key_code": binding.key,
-        "modifiers": {
-            "mandatory": [anchor_key.as_karabiner_modifier()],
-            "optional": ["caps_lock"]
+    let mut manipulators = Vec::new();
+    for modified in &binding.modified {
+        let var_base = format!("{}_{}", var_base, modified.modifiers_label().replace('+', "_"));
+        manipulators.extend(action_manipulators(
+            binding,
+            &modified.actions,
+            from(&modified.modifiers),
+            &var_base,
+            settings,
+        ));
+    }
+    if has_reverse_cycle(binding, settings) {
+        manipulators.extend(generate_cycling_manipulators(
+            binding,
+            &binding.actions,
+            from(&[ExtraModifier::Shift]),
+            var_base,
+            settings,
+            -1,
+        ));
+    }
+    let mut plain = action_manipulators(binding, &binding.actions, from(&[]), var_base, settings);
+    let double_tap = (!binding.double_tap_actions.is_empty()).then(|| {
+        let var_name = format!("{}_double_tap", var_base);
+        for manipulator in &mut plain {
+            arm_double_tap(manipulator, &var_name, settings.double_tap_timeout_ms);
+        }
+        double_tap_manipulator(binding, from(&[]), &var_name, settings)
+    });
+    if !binding.hold_actions.is_empty() {
+        let hold: Vec<Value> = binding
+            .hold_actions
+            .iter()
+            .flat_map(|a| action_to_karabiner(a, &settings.default_browser, settings.center_mouse))
+            .map(no_repeat)
+            .collect();
+        for manipulator in &mut plain {
+            add_hold(manipulator, &hold, settings.hold_threshold_ms);
         }
+    }
+    manipulators.extend(double_tap);
+    manipulators.extend(plain);
+    manipulators
+}
+
+/// Runs the double-tap actions when the key comes back while the first press has it armed.
+/// Must come before the binding's other manipulators.
+fn double_tap_manipulator(
+    binding: &Binding,
+    from: Value,
+    var_name: &str,
+    settings: &Settings,
+) -> Value {
+    let mut to: Vec<Value> = binding
+        .double_tap_actions
+        .iter()
+        .flat_map(|a| action_to_karabiner(a, &settings.default_browser, settings.center_mouse))
+        .collect();
+    to.push(json!({ "set_variable": { "name": var_name, "value": 0 } }));
+    let mut conditions = app_conditions(binding);
+    conditions.push(json!({ "type": "variable_if", "name": var_name, "value": 1 }));
+    json!({
+        "type": "basic",
+        "from": from,
+        "to": to,
+        "conditions": conditions
+    })
+}
+
+/// The first press still runs its actions, and arms the double tap until the timeout
+/// passes or another key is pressed. The delayed action belongs to the double tap;
+/// cycle resets are stamped into `to` instead (see `add_cycle_reset`).
+fn arm_double_tap(manipulator: &mut Value, var_name: &str, timeout_ms: u64) {
+    let disarm = json!([{ "set_variable": { "name": var_name, "value": 0 } }]);
+    if let Some(to) = manipulator["to"].as_array_mut() {
+        to.push(json!({ "set_variable": { "name": var_name, "value": 1 } }));
+    }
+    manipulator["to_delayed_action"] = json!({
+        "to_if_invoked": disarm,
+        "to_if_canceled": disarm
     });
+    manipulator["parameters"]["basic.to_delayed_action_delay_milliseconds"] = json!(timeout_ms);
+}
 
-    let to = if binding.actions.is_empty() {
-        vec![]
+/// Move a manipulator's events to `to_if_alone` and run `hold` once the key has been
+/// held for `threshold_ms`. The alone timeout matches the threshold, so a press runs
+/// exactly one of the two.
+fn add_hold(manipulator: &mut Value, hold: &[Value], threshold_ms: u64) {
+    manipulator["to_if_alone"] = manipulator["to"].take();
+    manipulator["to"] = json!([]);
+    manipulator["to_if_held_down"] = json!(hold);
+    manipulator["parameters"]["basic.to_if_alone_timeout_milliseconds"] = json!(threshold_ms);
+    manipulator["parameters"]["basic.to_if_held_down_threshold_milliseconds"] =
+        json!(threshold_ms);
+}
+
+/// Karabiner repeats key events in to_if_held_down for as long as the key is held;
+/// a hold action should fire once
+fn no_repeat(mut event: Value) -> Value {
+    if event.get("key_code").is_some() {
+        event["repeat"] = json!(false);
+    }
+    event
+}
+
+fn action_manipulators(
+    binding: &Binding,
+    actions: &[Action],
+    from: Value,
+    var_base: &str,
+    settings: &Settings,
+) -> Vec<Value> {
+    if actions.len() <= 1 || binding.mode == ActionMode::All {
+        // Single action, or every action at once - no cycling needed
+        generate_single_action_manipulators(binding, actions, from, settings)
     } else {
-        vec![action_to_karabiner(&binding.actions[0], default_browser, center_mouse)]
-    };
+        // Multiple actions - cycling
+        generate_cycling_manipulators(binding, actions, from, var_base, settings, 1)
+    }
+}
 
-    vec![json!({
+/// `from` event for anchor(+extra modifiers)+key
+fn from_event(key: &str, anchor_key: &AnchorKey, extra: &[ExtraModifier]) -> Value {
+    let mut mandatory = anchor_key.karabiner_modifiers();
+    mandatory.extend(extra.iter().map(|m| m.as_karabiner_modifier()));
+    json!({
+        "key_code": key,
+        "modifiers": {
+            "mandatory": mandatory,
+            "optional": ["caps_lock"]
+        }
+    })
+}
+
+/// frontmost_application_if / _unless conditions for a binding's app lists
+fn app_conditions(binding: &Binding) -> Vec<Value> {
+    let mut conditions = Vec::new();
+    if !binding.only_in.is_empty() {
+        conditions.push(json!({
+            "type": "frontmost_application_if",
+            "bundle_identifiers": binding.only_in.iter().map(|a| app_pattern(a)).collect::<Vec<_>>()
+        }));
+    }
+    if !binding.except_in.is_empty() {
+        conditions.push(json!({
+            "type": "frontmost_application_unless",
+            "bundle_identifiers": binding.except_in.iter().map(|a| app_pattern(a)).collect::<Vec<_>>()
+        }));
+    }
+    conditions
+}
+
+/// Karabiner matches bundle_identifiers as regexes: "/.../" entries are used verbatim,
+/// plain bundle IDs are escaped and anchored so "com.apple.Safari" doesn't match
+/// "com.apple.SafariTechnologyPreview"
+pub fn app_pattern(entry: &str) -> String {
+    if let Some(regex) = entry
+        .strip_prefix('/')
+        .and_then(|e| e.strip_suffix('/'))
+        .filter(|e| !e.is_empty())
+    {
+        return regex.to_string();
+    }
+    bundle_id_pattern(entry)
+}
+
+/// Exact-match regex for a bundle ID
+pub fn bundle_id_pattern(bundle_id: &str) -> String {
+    let mut pattern = String::from("^");
+    for c in bundle_id.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('$');
+    pattern
+}
+
+/// Generate the manipulator for a single action, or for all actions in order (no cycling)
+fn generate_single_action_manipulators(
+    binding: &Binding,
+    actions: &[Action],
+    from: Value,
+    settings: &Settings,
+) -> Vec<Value> {
+    let to: Vec<Value> = actions
+        .iter()
+        .flat_map(|a| action_to_karabiner(a, &settings.default_browser, settings.center_mouse))
+        .collect();
+
+    let mut manipulator = json!({
         "type": "basic",
         "from": from,
         "to": to
-    })]
+    });
+    let conditions = app_conditions(binding);
+    if !conditions.is_empty() {
+        manipulator["conditions"] = json!(conditions);
+    }
+    let mut manipulators: Vec<Value> = match actions {
+        [action] => toggle_manipulator(&manipulator, action).into_iter().collect(),
+        _ => Vec::new(),
+    };
+    manipulators.push(manipulator);
+    manipulators
+}
+
+/// For a toggle App action: a copy of its launch manipulator that hides the app
+/// instead when it is already frontmost. It keeps the launch manipulator's from,
+/// conditions and cycle step, and must come right before it.
+fn toggle_manipulator(launch: &Value, action: &Action) -> Option<Value> {
+    let Action::App {
+        bundle_id: Some(id),
+        toggle: true,
+        ..
+    } = action
+    else {
+        return None;
+    };
+    if id.is_empty() {
+        return None;
+    }
+
+    let mut hide = launch.clone();
+    hide["to"][0] = json!({ "shell_command": hide_command(id) });
+    let mut conditions = hide["conditions"].as_array().cloned().unwrap_or_default();
+    conditions.push(json!({
+        "type": "frontmost_application_if",
+        "bundle_identifiers": [bundle_id_pattern(id)]
+    }));
+    hide["conditions"] = json!(conditions);
+    Some(hide)
+}
+
+fn hide_command(bundle_id: &str) -> String {
+    let script = format!(
+        "tell application \"System Events\" to set visible of \
+         (first process whose bundle identifier is {}) to false",
+        applescript_string(bundle_id)
+    );
+    format!("osascript -e {}", shell_quote(&script))
 }
 
 /// Generate manipulators for cycling through multiple actions
 fn generate_cycling_manipulators(
     binding: &Binding,
-    anchor_key: &AnchorKey,
-    default_browser: &Browser,
-    center_mouse: CenterMouseMode,
+    actions: &[Action],
+    from: Value,
+    var_base: &str,
+    settings: &Settings,
+    step: isize,
 ) -> Vec<Value> {
-    let var_name = format!("{}{}_cycle", VAR_PREFIX, binding.key);
-    let num_actions = binding.actions.len();
+    let var_name = format!("{}_cycle", var_base);
+    let num_actions = actions.len();
+    let app_conditions = app_conditions(binding);
 
-    let from = json!({
-        "key_code": binding.key,
-        "modifiers": {
-            "mandatory": [anchor_key.as_karabiner_modifier()],
-            "optional": ["caps_lock"]
-        }
+    let mut manipulators = Vec::new();
+    if binding.focus_aware {
+        manipulators.extend(focus_aware_manipulators(
+            actions,
+            &from,
+            &var_name,
+            &app_conditions,
+            settings,
+            step,
+        ));
+    }
+
+    // The variable holds one past the index of the last action run (0..=n), and 0 for a
+    // fresh cycle, which starts at the first action or, backwards, at the last
+    // A toggle app landing on itself while frontmost hides instead, still advancing the cycle
+    manipulators.extend((0..=num_actions).flat_map(|i| {
+        let target = match i {
+            0 if step < 0 => num_actions - 1,
+            0 => 0,
+            _ => cycle_index(i - 1, step, num_actions),
+        };
+        let next_value = target + 1;
+        let mut to =
+            action_to_karabiner(&actions[target], &settings.default_browser, settings.center_mouse);
+        to.push(json!({
+            "set_variable": {
+                "name": var_name,
+                "value": next_value
+            }
+        }));
+
+        let mut conditions = app_conditions.clone();
+        conditions.push(json!({
+            "type": "variable_if",
+            "name": var_name,
+            "value": i
+        }));
+
+        let launch = json!({
+            "type": "basic",
+            "from": from.clone(),
+            "to": to,
+            "conditions": conditions
+        });
+        let hide = toggle_manipulator(&launch, &actions[target]);
+        hide.into_iter().chain([launch])
+    }));
+
+    if let Some(ms) = binding.cycle_reset(settings) {
+        add_cycle_reset(&mut manipulators, &var_name, ms);
+    }
+    manipulators
+}
+
+/// `index + delta`, wrapped around a cycle of `len` actions
+fn cycle_index(index: usize, delta: isize, len: usize) -> usize {
+    (index as isize + delta).rem_euclid(len as isize) as usize
+}
+
+/// Whether a cycling binding gets anchor+shift+key to step backwards. Skipped when
+/// the binding opted out, or when anchor+shift+key already means something else.
+fn has_reverse_cycle(binding: &Binding, settings: &Settings) -> bool {
+    binding.actions.len() > 1
+        && binding.mode == ActionMode::Cycle
+        && !binding.no_reverse_cycle
+        && !binding
+            .modified
+            .iter()
+            .any(|m| m.modifiers == [ExtraModifier::Shift])
+        && !settings
+            .anchor_key
+            .karabiner_modifiers()
+            .iter()
+            .any(|m| m.ends_with("shift"))
+}
+
+/// Restart the cycle when the key comes back more than `ms` after its last press.
+/// Every press stamps `{var}_at` with the current time, and copies of the fresh-cycle
+/// manipulators match ahead of the counter while that stamp is stale. A delayed action
+/// can't do this: Karabiner cancels it as soon as any other key is pressed.
+fn add_cycle_reset(manipulators: &mut Vec<Value>, var_name: &str, ms: u64) {
+    let stamp_name = format!("{}_at", var_name);
+    let fresh = json!({ "type": "variable_if", "name": var_name, "value": 0 });
+    let stale = json!({
+        "type": "expression_if",
+        "expression": format!("system.now.milliseconds - {} > {}", stamp_name, ms)
     });
 
-    binding
-        .actions
+    for manipulator in manipulators.iter_mut() {
+        manipulator["to"].as_array_mut().unwrap().push(json!({
+            "set_variable": { "name": stamp_name, "expression": "system.now.milliseconds" }
+        }));
+    }
+
+    let is_counter = |m: &Value| {
+        m["conditions"].as_array().unwrap().iter().any(|c| c["name"] == var_name)
+    };
+    let Some(first_counter) = manipulators.iter().position(is_counter) else {
+        return;
+    };
+    let restarts: Vec<Value> = manipulators[first_counter..]
+        .iter()
+        .filter(|m| m["conditions"].as_array().unwrap().contains(&fresh))
+        .map(|m| {
+            let mut restart = m.clone();
+            for condition in restart["conditions"].as_array_mut().unwrap() {
+                if *condition == fresh {
+                    *condition = stale.clone();
+                }
+            }
+            restart
+        })
+        .collect();
+    manipulators.splice(first_counter..first_counter, restarts);
+}
+
+/// For each App action with a bundle ID: while that app is frontmost, jump to the
+/// action after it (before it when `step` is -1). These come before the counter manipulators, which remain the
+/// fallback when none of the cycle's apps is frontmost.
+fn focus_aware_manipulators(
+    actions: &[Action],
+    from: &Value,
+    var_name: &str,
+    app_conditions: &[Value],
+    settings: &Settings,
+    step: isize,
+) -> Vec<Value> {
+    let num_actions = actions.len();
+    actions
         .iter()
         .enumerate()
-        .map(|(i, action)| {
-            let next_value = (i + 1) % num_actions;
-            let action_to = action_to_karabiner(action, default_browser, center_mouse);
+        .filter_map(|(i, action)| match action {
+            Action::App {
+                bundle_id: Some(id),
+                ..
+            } if !id.is_empty() => Some((i, id)),
+            _ => None,
+        })
+        .map(|(i, id)| {
+            let next = cycle_index(i, step, num_actions);
+            let mut conditions = app_conditions.to_vec();
+            conditions.push(json!({
+                "type": "frontmost_application_if",
+                "bundle_identifiers": [bundle_id_pattern(id)]
+            }));
+
+            let mut to = action_to_karabiner(
+                &actions[next],
+                &settings.default_browser,
+                settings.center_mouse,
+            );
+            to.push(json!({
+                "set_variable": {
+                    "name": var_name,
+                    "value": next + 1
+                }
+            }));
 
             json!({
                 "type": "basic",
                 "from": from.clone(),
-                "to": [
-                    action_to,
-                    {
-                        "set_variable": {
-                            "name": var_name,
-                            "value": next_value
-                        }
-                    }
-                ],
-                "conditions": [{
-                    "type": "variable_if",
-                    "name": var_name,
-                    "value": i
-                }]
+                "to": to,
+                "conditions": conditions
             })
         })
         .collect()
 }
 
-/// Convert our Action to Karabiner's to event
-fn action_to_karabiner(action: &Action, default_browser: &Browser, center_mouse: CenterMouseMode) -> Value {
-    match action {
-        Action::App { target, bundle_id } => {
+/// Convert our Action to Karabiner's to events (several for typed text)
+fn action_to_karabiner(
+    action: &Action,
+    default_browser: &Browser,
+    center_mouse: CenterMouseMode,
+) -> Vec<Value> {
+    let event = match action {
+        Action::App {
+            target, bundle_id, ..
+        } => {
             let launch_cmd = match bundle_id {
                 Some(id) if !id.is_empty() => match center_mouse {
-                    CenterMouseMode::Off => format!("open -b {}", id),
+                    CenterMouseMode::Off => format!("open -b {}", shell_quote(id)),
                     // $HOME expands at shell runtime — not tied to the save-time user path
                     mode => format!(
-                        "open -b {} && \"{}/center-mouse.sh\" '{}' '{}'",
-                        id, SCRIPTS_RUNTIME_DIR, id, mode.as_str()
+                        "open -b {} && \"{}/center-mouse.sh\" {} {}",
+                        shell_quote(id),
+                        SCRIPTS_RUNTIME_DIR,
+                        shell_quote(id),
+                        shell_quote(mode.as_str())
                     ),
                 },
-                _ => format!("open -a '{}'", target), // Fallback: no bundle ID, skip center_mouse
+                // Fallback: no bundle ID, skip center_mouse
+                _ => format!("open -a {}", shell_quote(target)),
             };
             json!({
                 "shell_command": launch_cmd
@@ -143,58 +715,185 @@ fn action_to_karabiner(action: &Action, default_browser: &Browser, center_mouse:
             target,
             match_type,
             browser,
+            profile,
+            options,
         } => {
             let browser = browser.as_ref().unwrap_or(default_browser);
-            json!({
-                "shell_command": format!(
-                    "\"{}/url-focus.sh\" '{}' '{}' '{}'",
-                    SCRIPTS_RUNTIME_DIR,
-                    target,
-                    match_type.as_str(),
-                    browser.as_str()
-                )
-            })
+            // Through the scripts/rcmdb launcher, whose path never changes
+            let mut cmd = format!(
+                "\"{}/rcmdb\" focus-url --match-type {} --browser {}",
+                SCRIPTS_RUNTIME_DIR,
+                shell_quote(match_type.as_str()),
+                shell_quote(browser.as_str())
+            );
+            if let Some(profile) = profile {
+                cmd.push_str(&format!(" --profile {}", shell_quote(profile)));
+            }
+            for (flag, set) in [
+                ("--ignore-query", options.ignore_query),
+                ("--ignore-fragment", options.ignore_fragment),
+                ("--ignore-trailing-slash", options.ignore_trailing_slash),
+            ] {
+                if set {
+                    cmd.push(' ');
+                    cmd.push_str(flag);
+                }
+            }
+            cmd.push_str(&format!(" -- {}", shell_quote(target)));
+            json!({ "shell_command": cmd })
         }
         Action::Shell { command } => {
             json!({
                 "shell_command": command
             })
         }
+        Action::Open { path, with_app } => json!({ "shell_command": open_command(path, with_app) }),
+        Action::Shortcut { name, input } => json!({ "shell_command": shortcut_command(name, input) }),
+        Action::AppleScript { script } => json!({ "shell_command": applescript_command(script) }),
+        // Karabiner's idiom for a pause: later events wait until vk_none is released
+        Action::Delay { ms } => json!({ "key_code": "vk_none", "hold_down_milliseconds": ms }),
+        Action::Text { text } => return text_events(text),
+        Action::Keystroke {
+            key_code,
+            modifiers,
+        } => {
+            let mut event = json!({ "key_code": key_code });
+            if !modifiers.is_empty() {
+                event["modifiers"] =
+                    json!(modifiers.iter().map(|m| m.as_karabiner_modifier()).collect::<Vec<_>>());
+            }
+            event
+        }
+    };
+    vec![event]
+}
+
+/// One key event per character of `text`. If any character has no key on a US
+/// layout, the whole text is pasted instead: shell commands run asynchronously,
+/// so mixing them with key events could type the pieces out of order.
+fn text_events(text: &str) -> Vec<Value> {
+    let keys: Option<Vec<(&str, bool)>> = text.chars().map(char_to_key).collect();
+    let Some(keys) = keys else {
+        return vec![json!({ "shell_command": paste_command(text) })];
+    };
+    keys.into_iter()
+        .map(|(key, shift)| {
+            if shift {
+                json!({ "key_code": key, "modifiers": ["shift"] })
+            } else {
+                json!({ "key_code": key })
+            }
+        })
+        .collect()
+}
+
+/// Put `text` on the pasteboard and send cmd+v
+fn paste_command(text: &str) -> String {
+    format!(
+        "printf '%s' {} | pbcopy && osascript -e 'tell application \"System Events\" to keystroke \"v\" using command down'",
+        shell_quote(text)
+    )
+}
+
+fn open_command(path: &str, with_app: &Option<String>) -> String {
+    match with_app {
+        Some(id) if !id.is_empty() => format!("open -b {} {}", shell_quote(id), shell_path(path)),
+        _ => format!("open {}", shell_path(path)),
     }
 }
 
-/// Apply our rules to karabiner.json
-/// This preserves existing rules and only replaces our [rcmdb] rules
-pub fn apply_to_karabiner(config: &Config) -> Result<()> {
-    let config_path = karabiner_config_path()?;
+/// `shortcuts run` only reads input from a file, so the input goes through a temp file
+fn shortcut_command(name: &str, input: &Option<String>) -> String {
+    match input {
+        None => format!("shortcuts run {}", shell_quote(name)),
+        Some(input) => format!(
+            "f=$(mktemp) && printf '%s' {} > \"$f\" && shortcuts run {} --input-path \"$f\"; rm -f \"$f\"",
+            shell_quote(input),
+            shell_quote(name)
+        ),
+    }
+}
 
-    // Load existing karabiner.json or create default
-    let mut kara_config: Value = if config_path.exists() {
-        let content = fs::read_to_string(&config_path)
-            .with_context(|| format!("Failed to read {:?}", config_path))?;
-        serde_json::from_str(&content).with_context(|| "Failed to parse karabiner.json")?
-    } else {
-        json!({
-            "global": {},
-            "profiles": [{
-                "name": "Default",
-                "complex_modifications": {
-                    "rules": []
-                },
-                "simple_modifications": []
-            }]
+fn applescript_command(script: &AppleScript) -> String {
+    match script {
+        AppleScript::Source(source) => format!("osascript -e {}", shell_quote(source)),
+        AppleScript::File(path) => format!("osascript {}", shell_path(path)),
+    }
+}
+
+fn default_karabiner_config() -> Value {
+    json!({
+        "global": {},
+        "profiles": [{
+            "name": "Default",
+            "complex_modifications": {
+                "rules": []
+            },
+            "simple_modifications": []
+        }]
+    })
+}
+
+/// Load karabiner.json from `path`, or the default document if it does not exist
+pub fn load_karabiner_config(path: &Path) -> Result<Value> {
+    if !path.exists() {
+        return Ok(default_karabiner_config());
+    }
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {:?}", path))
+}
+
+/// Names of the profiles in a karabiner.json document
+pub fn profile_names(kara_config: &Value) -> Vec<String> {
+    kara_config
+        .get("profiles")
+        .and_then(|p| p.as_array())
+        .map(|profiles| {
+            profiles
+                .iter()
+                .map(|p| p.get("name").and_then(|n| n.as_str()).unwrap_or("").to_string())
+                .collect()
         })
-    };
+        .unwrap_or_default()
+}
 
-    // Create backup before modifying
-    create_backup()?;
+/// Indices of the profiles our rules go into: the named ones from settings,
+/// or the first profile when none are configured
+pub fn target_profile_indices(kara_config: &Value, profiles: &[String]) -> Result<Vec<usize>> {
+    if profiles.is_empty() {
+        return Ok(vec![0]);
+    }
+
+    let names = profile_names(kara_config);
+    profiles
+        .iter()
+        .map(|wanted| {
+            names.iter().position(|n| n == wanted).with_context(|| {
+                format!(
+                    "Karabiner profile '{}' not found (available: {})",
+                    wanted,
+                    names.join(", ")
+                )
+            })
+        })
+        .collect()
+}
+
+/// Return a copy of `kara_config` with our [rcmdb] rules replaced by freshly generated ones
+/// in each target profile. Other profiles are left untouched.
+/// Pure: shared by `apply_to_karabiner` and `rcmdb diff`.
+pub fn build_karabiner_config(kara_config: &Value, config: &Config) -> Result<Value> {
+    let mut kara_config = kara_config.clone();
+    let targets = target_profile_indices(&kara_config, &config.settings.profiles)?;
+    let our_rules = generate_rules(config);
 
-    // Find the first profile (or create one)
     let profiles = kara_config
         .get_mut("profiles")
         .and_then(|p| p.as_array_mut())
         .context("karabiner.json missing profiles array")?;
 
+    // Create a profile if there is none (only reachable when no profiles are configured)
     if profiles.is_empty() {
         profiles.push(json!({
             "name": "Default",
@@ -205,13 +904,21 @@ pub fn apply_to_karabiner(config: &Config) -> Result<()> {
         }));
     }
 
-    let profile = profiles.get_mut(0).context("No profile found")?;
+    for idx in targets {
+        let profile = profiles.get_mut(idx).context("No profile found")?;
+        replace_profile_rules(profile, &our_rules)?;
+    }
+
+    Ok(kara_config)
+}
 
+/// Swap the [rcmdb] rules of one profile for `our_rules`, keeping everything else
+fn replace_profile_rules(profile: &mut Value, our_rules: &[Value]) -> Result<()> {
     // Ensure complex_modifications exists
     if profile.get("complex_modifications").is_none() {
         profile
             .as_object_mut()
-            .unwrap()
+            .context("profile is not an object")?
             .insert("complex_modifications".to_string(), json!({ "rules": [] }));
     }
 
@@ -231,18 +938,33 @@ pub fn apply_to_karabiner(config: &Config) -> Result<()> {
         .context("rules is not an array")?;
 
     // Remove existing [rcmdb] rules
-    rules.retain(|rule| {
-        rule.get("description")
-            .and_then(|d| d.as_str())
-            .map(|d| !d.starts_with(RULE_PREFIX))
-            .unwrap_or(true)
-    });
+    rules.retain(|rule| !is_rcmdb_rule(rule));
 
     // Add our new rules
-    let our_rules = generate_rules(config);
-    for rule in our_rules {
-        rules.push(rule);
-    }
+    rules.extend(our_rules.iter().cloned());
+
+    Ok(())
+}
+
+/// True if the rule was generated by us (description starts with [rcmdb])
+pub fn is_rcmdb_rule(rule: &Value) -> bool {
+    rule.get("description")
+        .and_then(|d| d.as_str())
+        .map(|d| d.starts_with(RULE_PREFIX))
+        .unwrap_or(false)
+}
+
+/// Apply our rules to karabiner.json
+/// This preserves existing rules and only replaces our [rcmdb] rules
+pub fn apply_to_karabiner(config: &Config) -> Result<()> {
+    let config_path = karabiner_config_path()?;
+
+    // Load existing karabiner.json or create default
+    let current = load_karabiner_config(&config_path)?;
+    let kara_config = build_karabiner_config(&current, config)?;
+
+    // Create backup before modifying
+    create_backup()?;
 
     // Write back to karabiner.json
     let output = serde_json::to_string_pretty(&kara_config)?;
@@ -261,7 +983,8 @@ pub fn apply_to_karabiner(config: &Config) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::Binding;
+    use crate::config::{Binding, KeyModifier, ModifiedActions};
+    use std::collections::HashSet;
 
     #[test]
     fn test_single_action_rule() {
@@ -271,10 +994,12 @@ mod tests {
             actions: vec![Action::App {
                 target: "Terminal".to_string(),
                 bundle_id: Some("com.apple.Terminal".to_string()),
+                toggle: false,
             }],
+            ..Default::default()
         };
 
-        let rule = generate_binding_rule(&binding, &AnchorKey::RightCommand, &Browser::Firefox, CenterMouseMode::Off);
+        let rule = generate_binding_rule(&binding, "rcmdb_t", &Settings::default());
         assert!(rule["description"].as_str().unwrap().contains("[rcmdb]"));
         assert_eq!(rule["manipulators"].as_array().unwrap().len(), 1);
     }
@@ -288,21 +1013,927 @@ mod tests {
                 Action::App {
                     target: "Terminal".to_string(),
                     bundle_id: Some("com.apple.Terminal".to_string()),
+                    toggle: false,
                 },
                 Action::App {
                     target: "iTerm".to_string(),
                     bundle_id: Some("com.googlecode.iterm2".to_string()),
+                    toggle: false,
                 },
             ],
+            ..Default::default()
         };
 
-        let rule = generate_binding_rule(&binding, &AnchorKey::RightCommand, &Browser::Firefox, CenterMouseMode::Off);
+        let rule = generate_binding_rule(&binding, "rcmdb_t", &Settings::default());
         let manipulators = rule["manipulators"].as_array().unwrap();
-        assert_eq!(manipulators.len(), 2);
+        // 3 reverse (shift) + 3 forward: variable values 0 (fresh), 1 and 2
+        assert_eq!(manipulators.len(), 6);
 
-        // Check cycling variables
-        assert!(manipulators[0]["to"][1]["set_variable"]["value"] == 1);
-        assert!(manipulators[1]["to"][1]["set_variable"]["value"] == 0);
+        // Check cycling variables: one past the action run, wrapping back to the first
+        assert!(manipulators[3]["to"][1]["set_variable"]["value"] == 1);
+        assert!(manipulators[4]["to"][1]["set_variable"]["value"] == 2);
+        assert!(manipulators[5]["to"][1]["set_variable"]["value"] == 1);
+        assert_eq!(manipulators[5]["to"][0], manipulators[3]["to"][0]);
+    }
+
+    #[test]
+    fn test_focus_aware_cycle() {
+        let app = |name: &str, id: Option<&str>| Action::App {
+            target: name.to_string(),
+            bundle_id: id.map(String::from),
+            toggle: false,
+        };
+        let binding = Binding {
+            key: "t".to_string(),
+            actions: vec![
+                app("Terminal", Some("com.apple.Terminal")),
+                app("Legacy", None),
+                app("iTerm", Some("com.googlecode.iterm2")),
+            ],
+            focus_aware: true,
+            ..Default::default()
+        };
+
+        let rule = generate_binding_rule(&binding, "rcmdb_t", &Settings::default());
+        let manipulators = rule["manipulators"].as_array().unwrap();
+        // 2 focus-aware (apps with bundle IDs) + 4 counter fallbacks, reverse first
+        assert_eq!(manipulators.len(), 12);
+        let (reverse, manipulators) = manipulators.split_at(6);
+
+        // Reverse: Terminal frontmost wraps back to iTerm, counter continues at Terminal
+        assert_eq!(reverse[0]["to"][0]["shell_command"], "open -b 'com.googlecode.iterm2'");
+        assert_eq!(reverse[0]["to"][1]["set_variable"]["value"], 3);
+
+        // Terminal frontmost -> Legacy, counter continues at iTerm
+        let terminal = &manipulators[0];
+        assert_eq!(terminal["conditions"][0]["type"], "frontmost_application_if");
+        assert_eq!(
+            terminal["conditions"][0]["bundle_identifiers"],
+            json!(["^com\\.apple\\.Terminal$"])
+        );
+        assert_eq!(terminal["to"][0]["shell_command"], "open -a 'Legacy'");
+        assert_eq!(terminal["to"][1]["set_variable"]["value"], 2);
+
+        // iTerm frontmost wraps around to Terminal
+        let iterm = &manipulators[1];
+        assert_eq!(iterm["to"][0]["shell_command"], "open -b 'com.apple.Terminal'");
+        assert_eq!(iterm["to"][1]["set_variable"]["value"], 1);
+
+        assert_eq!(manipulators[2]["conditions"][0]["type"], "variable_if");
+    }
+
+    #[test]
+    fn test_cycle_reset() {
+        let settings = Settings {
+            cycle_reset_ms: 800,
+            ..Settings::default()
+        };
+        let stamp = json!({
+            "set_variable": { "name": "rcmdb_c_cycle_at", "expression": "system.now.milliseconds" }
+        });
+
+        // Global default applies to cycles
+        let binding = Binding {
+            no_reverse_cycle: true,
+            ..shell_binding("c", &["a", "b"])
+        };
+        let rule = generate_binding_rule(&binding, "rcmdb_c", &settings);
+        let manipulators = rule["manipulators"].as_array().unwrap();
+        // Restart, then the counter for values 0 to 2
+        assert_eq!(manipulators.len(), 4);
+        for m in manipulators {
+            assert_eq!(m["to"].as_array().unwrap().last().unwrap(), &stamp);
+            // Not a delayed action: Karabiner cancels those on any other key press, so
+            // typing elsewhere in between would keep the cycle from ever restarting
+            assert!(m.get("to_delayed_action").is_none());
+        }
+        let restart = &manipulators[0];
+        assert_eq!(
+            restart["conditions"],
+            json!([{
+                "type": "expression_if",
+                "expression": "system.now.milliseconds - rcmdb_c_cycle_at > 800"
+            }])
+        );
+        // A stale press runs the first action and moves on, like a fresh cycle
+        assert_eq!(restart["to"], manipulators[1]["to"]);
+        assert_eq!(restart["to"][0]["shell_command"], "a");
+        assert_eq!(manipulators[1]["conditions"][0]["value"], 0);
+        assert_eq!(manipulators[3]["conditions"][0]["value"], 2);
+
+        // Per-binding override, and 0 turns it off
+        let binding = Binding {
+            cycle_reset_ms: Some(300),
+            ..binding
+        };
+        let rule = generate_binding_rule(&binding, "rcmdb_c", &settings);
+        assert_eq!(
+            rule["manipulators"][0]["conditions"][0]["expression"],
+            "system.now.milliseconds - rcmdb_c_cycle_at > 300"
+        );
+        let binding = Binding {
+            cycle_reset_ms: Some(0),
+            ..binding
+        };
+        let rule = generate_binding_rule(&binding, "rcmdb_c", &settings);
+        assert_eq!(rule["manipulators"].as_array().unwrap().len(), 3);
+        assert!(!rule.to_string().contains("rcmdb_c_cycle_at"));
+
+        // Single actions have nothing to reset
+        let rule = generate_binding_rule(&shell_binding("s", &["a"]), "rcmdb_s", &settings);
+        assert_eq!(rule["manipulators"].as_array().unwrap().len(), 1);
+        assert!(!rule.to_string().contains("expression"));
+    }
+
+    #[test]
+    fn test_reverse_cycle() {
+        let rule = generate_binding_rule(
+            &shell_binding("c", &["a", "b", "c"]),
+            "rcmdb_c",
+            &Settings::default(),
+        );
+        let manipulators = rule["manipulators"].as_array().unwrap();
+        assert_eq!(manipulators.len(), 8);
+
+        // (variable value, command run, new value): the variable points one past the
+        // last action run, so shift runs the one before it; a fresh cycle starts at the end
+        let expected = [(0, "c", 3), (1, "c", 3), (2, "a", 1), (3, "b", 2)];
+        for (m, (value, command, next)) in manipulators[..4].iter().zip(expected) {
+            assert_eq!(
+                m["from"]["modifiers"]["mandatory"],
+                json!(["right_command", "shift"])
+            );
+            assert_eq!(m["conditions"][0]["name"], "rcmdb_c_cycle");
+            assert_eq!(m["conditions"][0]["value"], value);
+            assert_eq!(m["to"][0]["shell_command"], command);
+            assert_eq!(m["to"][1]["set_variable"]["value"], next);
+        }
+        // Forward manipulators are unchanged
+        assert_eq!(manipulators[4]["to"][0]["shell_command"], "a");
+        assert_eq!(manipulators[6]["to"][0]["shell_command"], "c");
+
+        // Press the keys the way Karabiner would: the first manipulator whose
+        // variable condition holds runs and moves the variable on
+        let press = |shift: bool, value: &mut u64| {
+            let m = manipulators
+                .iter()
+                .filter(|m| m["from"]["modifiers"]["mandatory"].as_array().unwrap().len() == 2)
+                .map(|m| (true, m))
+                .chain(manipulators[4..].iter().map(|m| (false, m)))
+                .find(|(reverse, m)| *reverse == shift && m["conditions"][0]["value"] == *value)
+                .unwrap()
+                .1;
+            *value = m["to"][1]["set_variable"]["value"].as_u64().unwrap();
+            m["to"][0]["shell_command"].as_str().unwrap()
+        };
+        let mut value = 0;
+        let reversed: Vec<&str> = (0..4).map(|_| press(true, &mut value)).collect();
+        assert_eq!(reversed, ["c", "b", "a", "c"]);
+        let forward: Vec<&str> = (0..2).map(|_| press(false, &mut value)).collect();
+        assert_eq!(forward, ["a", "b"]);
+        assert_eq!(press(true, &mut value), "a");
+    }
+
+    #[test]
+    fn test_reverse_cycle_skipped() {
+        let count = |binding: &Binding, settings: &Settings| {
+            generate_binding_rule(binding, "rcmdb_c", settings)["manipulators"]
+                .as_array()
+                .unwrap()
+                .len()
+        };
+        let settings = Settings::default();
+
+        let opted_out = Binding {
+            no_reverse_cycle: true,
+            ..shell_binding("c", &["a", "b", "c"])
+        };
+        assert_eq!(count(&opted_out, &settings), 4);
+
+        // anchor+shift+key already has its own actions
+        let shift_taken = Binding {
+            modified: vec![ModifiedActions {
+                modifiers: vec![ExtraModifier::Shift],
+                actions: vec![Action::Shell {
+                    command: "x".to_string(),
+                }],
+            }],
+            ..shell_binding("c", &["a", "b", "c"])
+        };
+        assert_eq!(count(&shift_taken, &settings), 5);
+
+        // Hyper already includes shift
+        let hyper = Settings {
+            anchor_key: AnchorKey::Hyper,
+            ..Settings::default()
+        };
+        assert_eq!(count(&shell_binding("c", &["a", "b", "c"]), &hyper), 4);
+
+        // Nothing to reverse for a single action
+        assert_eq!(count(&shell_binding("c", &["a"]), &settings), 1);
+    }
+
+    #[test]
+    fn test_keystroke_action() {
+        let binding = Binding {
+            key: "4".to_string(),
+            actions: vec![
+                Action::Keystroke {
+                    key_code: "4".to_string(),
+                    modifiers: vec![KeyModifier::Command, KeyModifier::Shift],
+                },
+                Action::Keystroke {
+                    key_code: "escape".to_string(),
+                    modifiers: vec![],
+                },
+            ],
+            ..Default::default()
+        };
+
+        let rule = generate_binding_rule(&binding, "rcmdb_4", &Settings::default());
+        let forward = &rule["manipulators"].as_array().unwrap()[3..];
+        assert_eq!(
+            forward[0]["to"][0],
+            json!({"key_code": "4", "modifiers": ["command", "shift"]})
+        );
+        assert_eq!(forward[0]["to"][1]["set_variable"]["value"], 1);
+        assert_eq!(forward[1]["to"][0], json!({"key_code": "escape"}));
+    }
+
+    #[test]
+    fn test_text_action() {
+        let events = text_events("Hi, 2!");
+        assert_eq!(
+            json!(events),
+            json!([
+                {"key_code": "h", "modifiers": ["shift"]},
+                {"key_code": "i"},
+                {"key_code": "comma"},
+                {"key_code": "spacebar"},
+                {"key_code": "2"},
+                {"key_code": "1", "modifiers": ["shift"]}
+            ])
+        );
+
+        // In a cycle the events come before the set_variable
+        let binding = Binding {
+            key: "e".to_string(),
+            actions: vec![
+                Action::Text { text: "ab".to_string() },
+                Action::Text { text: "c".to_string() },
+            ],
+            no_reverse_cycle: true,
+            ..Default::default()
+        };
+        let rule = generate_binding_rule(&binding, "rcmdb_e", &Settings::default());
+        let to = rule["manipulators"][0]["to"].as_array().unwrap();
+        assert_eq!(to.len(), 3);
+        assert_eq!(to[1]["key_code"], "b");
+        assert_eq!(to[2]["set_variable"]["value"], 1);
+    }
+
+    #[test]
+    fn test_text_action_paste_fallback() {
+        // One unmapped character sends the whole text through the pasteboard
+        let events = text_events("café's");
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0]["shell_command"],
+            "printf '%s' 'café'\\''s' | pbcopy && osascript -e \
+             'tell application \"System Events\" to keystroke \"v\" using command down'"
+        );
+    }
+
+    #[test]
+    fn test_shortcut_and_applescript_quoting() {
+        let command = |action: Action| {
+            action_to_karabiner(&action, &Browser::Firefox, CenterMouseMode::Off)[0]["shell_command"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(
+            command(Action::Shortcut {
+                name: "Bob's \"Focus\"".to_string(),
+                input: None,
+            }),
+            "shortcuts run 'Bob'\\''s \"Focus\"'"
+        );
+        assert_eq!(
+            command(Action::Shortcut {
+                name: "Log".to_string(),
+                input: Some("$HOME\n`x`".to_string()),
+            }),
+            "f=$(mktemp) && printf '%s' '$HOME\n`x`' > \"$f\" && \
+             shortcuts run 'Log' --input-path \"$f\"; rm -f \"$f\""
+        );
+        assert_eq!(
+            command(Action::AppleScript {
+                script: AppleScript::Source(
+                    "tell application \"Finder\"\n  display dialog \"it's\"\nend tell"
+                        .to_string()
+                ),
+            }),
+            "osascript -e 'tell application \"Finder\"\n  display dialog \"it'\\''s\"\nend tell'"
+        );
+        assert_eq!(
+            command(Action::AppleScript {
+                script: AppleScript::File("~/My Scripts/a.scpt".to_string()),
+            }),
+            "osascript \"$HOME\"'/My Scripts/a.scpt'"
+        );
+        assert_eq!(
+            command(Action::AppleScript {
+                script: AppleScript::File("/tmp/a.scpt".to_string()),
+            }),
+            "osascript '/tmp/a.scpt'"
+        );
+    }
+
+    #[test]
+    fn test_open_action() {
+        let command = |path: &str, with_app: Option<&str>| {
+            let action = Action::Open {
+                path: path.to_string(),
+                with_app: with_app.map(String::from),
+            };
+            action_to_karabiner(&action, &Browser::Firefox, CenterMouseMode::Off)[0]["shell_command"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(command("~/work", None), "open \"$HOME\"'/work'");
+        assert_eq!(command("$HOME", None), "open \"$HOME\"");
+        assert_eq!(
+            command("$HOME/notes/it's.md", Some("com.microsoft.VSCode")),
+            "open -b 'com.microsoft.VSCode' \"$HOME\"'/notes/it'\\''s.md'"
+        );
+        assert_eq!(command("/Volumes/My Disk", None), "open '/Volumes/My Disk'");
+        // Only a whole leading component counts as home
+        assert_eq!(command("~work", None), "open '~work'");
+    }
+
+    #[test]
+    fn test_all_mode() {
+        let mut binding = Binding {
+            mode: ActionMode::All,
+            focus_aware: true,
+            ..shell_binding("w", &["open-ide", "open-dashboard"])
+        };
+        binding.actions.insert(1, Action::Delay { ms: 300 });
+        assert_eq!(binding.actions_summary(), "$ open-ide + wait 300ms + $ open-dashboard");
+
+        let rule = generate_binding_rule(&binding, "rcmdb_w", &Settings::default());
+        let manipulators = rule["manipulators"].as_array().unwrap();
+        // One manipulator, no cycle variable and no reverse cycle
+        assert_eq!(manipulators.len(), 1);
+        assert!(manipulators[0].get("conditions").is_none());
+        assert_eq!(
+            manipulators[0]["to"],
+            json!([
+                {"shell_command": "open-ide"},
+                {"key_code": "vk_none", "hold_down_milliseconds": 300},
+                {"shell_command": "open-dashboard"}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_hold_actions() {
+        let binding = Binding {
+            hold_actions: vec![Action::Keystroke {
+                key_code: "n".to_string(),
+                modifiers: vec![KeyModifier::Command],
+            }],
+            cycle_reset_ms: Some(2000),
+            ..shell_binding("t", &["a", "b"])
+        };
+        assert_eq!(binding.hold_summary().as_deref(), Some("hold: ⌨ cmd+n"));
+        let settings = Settings {
+            hold_threshold_ms: 300,
+            ..Settings::default()
+        };
+
+        let rule = generate_binding_rule(&binding, "rcmdb_t", &settings);
+        let manipulators = rule["manipulators"].as_array().unwrap();
+        // Reverse cycle, then the plain cycle, each with its restart; only the plain
+        // anchor+key holds
+        assert_eq!(manipulators.len(), 8);
+        for m in &manipulators[..4] {
+            assert!(m.get("to_if_held_down").is_none());
+        }
+        for m in &manipulators[4..] {
+            assert_eq!(m["to"], json!([]));
+            // The action, the cycle step and the cycle reset stamp all wait for the tap
+            assert_eq!(m["to_if_alone"].as_array().unwrap().len(), 3);
+            assert_eq!(
+                m["to_if_held_down"],
+                json!([{"key_code": "n", "modifiers": ["command"], "repeat": false}])
+            );
+            assert_eq!(m["parameters"]["basic.to_if_held_down_threshold_milliseconds"], 300);
+            assert_eq!(m["parameters"]["basic.to_if_alone_timeout_milliseconds"], 300);
+            assert_eq!(m["to_if_alone"][2]["set_variable"]["name"], "rcmdb_t_cycle_at");
+        }
+        assert_eq!(manipulators[4]["to_if_alone"][0]["shell_command"], "a");
+
+        // Leader second strokes still disarm on key down
+        let sequence = Binding {
+            sequence: Some("p".to_string()),
+            ..binding
+        };
+        let rule = generate_leader_rule("t", &[(&sequence, "rcmdb_t_p".to_string())], &settings);
+        let manipulators = rule["manipulators"].as_array().unwrap();
+        let step = manipulators.iter().rev().nth(1 + MODIFIER_KEY_CODES.len()).unwrap();
+        assert_eq!(step["to"], json!([{"set_variable": {"name": "rcmdb_leader_t", "value": 0}}]));
+        assert!(step.get("to_if_held_down").is_some());
+    }
+
+    #[test]
+    fn test_double_tap_actions() {
+        let binding = Binding {
+            double_tap_actions: vec![Action::Keystroke {
+                key_code: "w".to_string(),
+                modifiers: vec![KeyModifier::Command],
+            }],
+            only_in: vec!["com.apple.Terminal".to_string()],
+            cycle_reset_ms: Some(2000),
+            ..shell_binding("t", &["a", "b"])
+        };
+        assert_eq!(binding.double_tap_summary().as_deref(), Some("2×: ⌨ cmd+w"));
+
+        let rule = generate_binding_rule(&binding, "rcmdb_t", &Settings::default());
+        let manipulators = rule["manipulators"].as_array().unwrap();
+        // Reverse cycle with its restart, double tap, then the plain cycle with its restart
+        assert_eq!(manipulators.len(), 9);
+        let double_tap = &manipulators[4];
+        assert_eq!(
+            double_tap["to"],
+            json!([
+                {"key_code": "w", "modifiers": ["command"]},
+                {"set_variable": {"name": "rcmdb_t_double_tap", "value": 0}}
+            ])
+        );
+        assert_eq!(double_tap["conditions"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            double_tap["conditions"][1],
+            json!({"type": "variable_if", "name": "rcmdb_t_double_tap", "value": 1})
+        );
+
+        for m in &manipulators[5..] {
+            let to = m["to"].as_array().unwrap();
+            assert!(to[0]["shell_command"].is_string());
+            assert_eq!(
+                to.last().unwrap(),
+                &json!({"set_variable": {"name": "rcmdb_t_double_tap", "value": 1}})
+            );
+            let disarm = json!([{"set_variable": {"name": "rcmdb_t_double_tap", "value": 0}}]);
+            assert_eq!(m["to_delayed_action"]["to_if_invoked"], disarm);
+            assert_eq!(m["to_delayed_action"]["to_if_canceled"], disarm);
+            assert_eq!(m["parameters"]["basic.to_delayed_action_delay_milliseconds"], 300);
+            // Arming the double tap keeps the cycle reset
+            assert_eq!(to[to.len() - 2]["set_variable"]["name"], "rcmdb_t_cycle_at");
+        }
+        assert_eq!(
+            manipulators[5]["conditions"][1],
+            json!({
+                "type": "expression_if",
+                "expression": "system.now.milliseconds - rcmdb_t_cycle_at > 2000"
+            })
+        );
+        // The reverse cycle doesn't arm the double tap
+        for m in &manipulators[..4] {
+            assert!(m.get("to_delayed_action").is_none());
+        }
+    }
+
+    #[test]
+    fn test_double_tap_variables_dont_collide_with_cycles() {
+        let double_tap = |binding: Binding| Binding {
+            double_tap_actions: vec![Action::Shell {
+                command: "x".to_string(),
+            }],
+            ..binding
+        };
+        let config = Config {
+            bindings: vec![
+                double_tap(shell_binding("t", &["a", "b"])),
+                double_tap(Binding {
+                    only_in: vec!["com.apple.Safari".to_string()],
+                    ..shell_binding("t", &["c", "d"])
+                }),
+                double_tap(Binding {
+                    modified: vec![ModifiedActions {
+                        modifiers: vec![ExtraModifier::Shift],
+                        actions: vec![
+                            Action::Shell { command: "e".to_string() },
+                            Action::Shell { command: "f".to_string() },
+                        ],
+                    }],
+                    ..shell_binding("d", &["g", "h"])
+                }),
+            ],
+            ..Default::default()
+        };
+
+        let mut cycle_vars = HashSet::new();
+        let mut double_tap_vars = HashSet::new();
+        for rule in generate_rules(&config) {
+            for m in rule["manipulators"].as_array().unwrap() {
+                let conditions = m["conditions"].as_array().into_iter().flatten();
+                for condition in conditions.filter(|c| c["type"] == "variable_if") {
+                    let name = condition["name"].as_str().unwrap().to_string();
+                    if name.ends_with("_double_tap") {
+                        double_tap_vars.insert(name);
+                    } else {
+                        cycle_vars.insert(name);
+                    }
+                }
+            }
+        }
+        assert_eq!(
+            double_tap_vars,
+            HashSet::from([
+                "rcmdb_t_double_tap".to_string(),
+                "rcmdb_t_2_double_tap".to_string(),
+                "rcmdb_d_double_tap".to_string(),
+            ])
+        );
+        assert!(cycle_vars.iter().all(|v| v.ends_with("_cycle")));
+        assert_eq!(cycle_vars.len(), 4);
+        assert!(cycle_vars.is_disjoint(&double_tap_vars));
+    }
+
+    fn toggle_app(name: &str, id: Option<&str>, toggle: bool) -> Action {
+        Action::App {
+            target: name.to_string(),
+            bundle_id: id.map(String::from),
+            toggle,
+        }
+    }
+
+    #[test]
+    fn test_toggle_app_single() {
+        for center_mouse in [
+            CenterMouseMode::Off,
+            CenterMouseMode::Always,
+            CenterMouseMode::MultiMonitorOnly,
+        ] {
+            let settings = Settings {
+                center_mouse,
+                ..Settings::default()
+            };
+            let binding = Binding {
+                key: "n".to_string(),
+                actions: vec![toggle_app("Notes", Some("com.apple.Notes"), true)],
+                except_in: vec!["com.vmware.fusion".to_string()],
+                ..Default::default()
+            };
+            let rule = generate_binding_rule(&binding, "rcmdb_n", &settings);
+            let manipulators = rule["manipulators"].as_array().unwrap();
+            assert_eq!(manipulators.len(), 2);
+
+            // Hide first: same from and app conditions, plus Notes frontmost
+            let (hide, launch) = (&manipulators[0], &manipulators[1]);
+            assert_eq!(hide["from"], launch["from"]);
+            assert_eq!(hide["conditions"][0], launch["conditions"][0]);
+            assert_eq!(
+                hide["conditions"][1],
+                json!({
+                    "type": "frontmost_application_if",
+                    "bundle_identifiers": ["^com\\.apple\\.Notes$"]
+                })
+            );
+            let hide_cmd = hide["to"][0]["shell_command"].as_str().unwrap();
+            assert!(hide_cmd.contains("bundle identifier is \"com.apple.Notes\") to false"));
+            assert!(!hide_cmd.contains("center-mouse"), "hiding never moves the mouse");
+
+            let launch_cmd = launch["to"][0]["shell_command"].as_str().unwrap();
+            assert!(launch_cmd.starts_with("open -b 'com.apple.Notes'"));
+            assert_eq!(
+                launch_cmd.contains("center-mouse.sh"),
+                center_mouse != CenterMouseMode::Off
+            );
+        }
+    }
+
+    #[test]
+    fn test_toggle_needs_flag_and_bundle_id() {
+        for action in [
+            toggle_app("Notes", Some("com.apple.Notes"), false),
+            toggle_app("Notes", None, true),
+            toggle_app("Notes", Some(""), true),
+        ] {
+            let binding = Binding {
+                key: "n".to_string(),
+                actions: vec![action],
+                ..Default::default()
+            };
+            let rule = generate_binding_rule(&binding, "rcmdb_n", &Settings::default());
+            assert_eq!(rule["manipulators"].as_array().unwrap().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_toggle_app_in_cycle() {
+        let binding = Binding {
+            key: "t".to_string(),
+            actions: vec![
+                toggle_app("Terminal", Some("com.apple.Terminal"), true),
+                toggle_app("iTerm", Some("com.googlecode.iterm2"), false),
+            ],
+            cycle_reset_ms: Some(500),
+            ..Default::default()
+        };
+        let settings = Settings {
+            center_mouse: CenterMouseMode::Always,
+            ..Settings::default()
+        };
+        let rule = generate_binding_rule(&binding, "rcmdb_t", &settings);
+        let manipulators = rule["manipulators"].as_array().unwrap();
+        // Forward: [hide Terminal, launch Terminal] restarting a stale cycle, then by value
+        // 0 -> [hide, launch] Terminal, 1 -> iTerm, 2 -> [hide, launch] Terminal. Backwards
+        // a fresh cycle starts at iTerm: [restart, 0, 1] -> iTerm, 2 -> [hide, launch] Terminal
+        assert_eq!(manipulators.len(), 12);
+        let (reverse, forward) = manipulators.split_at(5);
+        let (restart, steps) = forward.split_at(2);
+        for (restart, counter) in restart.iter().zip(steps) {
+            assert_eq!(restart["to"], counter["to"]);
+            assert_eq!(restart["conditions"][0]["type"], "expression_if");
+            assert_eq!(restart["conditions"][1], counter["conditions"][1]);
+        }
+        assert_eq!(reverse[0]["conditions"][0]["type"], "expression_if");
+
+        let pairs = [(&steps[0], &steps[1]), (&steps[3], &steps[4]), (&reverse[3], &reverse[4])];
+        for (hide, launch) in pairs {
+            // Only when the cycle is about to launch Terminal and Terminal is in front
+            assert_eq!(hide["conditions"][0], launch["conditions"][0]);
+            assert_eq!(hide["conditions"][1]["type"], "frontmost_application_if");
+            // Hiding advances the cycle like the launch would, and keeps the reset
+            assert_eq!(hide["to"][1], launch["to"][1]);
+            assert_eq!(hide["to"][1]["set_variable"]["value"], 1);
+            assert_eq!(hide["to"][2], launch["to"][2]);
+            assert!(hide["to"][0]["shell_command"].as_str().unwrap().contains("visible"));
+            assert!(launch["to"][0]["shell_command"]
+                .as_str()
+                .unwrap()
+                .contains("center-mouse.sh"));
+        }
+
+        // iTerm has no toggle
+        for iterm in [&steps[2], &reverse[0], &reverse[1], &reverse[2]] {
+            assert_eq!(iterm["conditions"].as_array().unwrap().len(), 1);
+            assert!(iterm["to"][0]["shell_command"]
+                .as_str()
+                .unwrap()
+                .starts_with("open -b 'com.googlecode.iterm2'"));
+        }
+        assert_eq!(forward[0]["from"]["modifiers"]["mandatory"], json!(["right_command"]));
+        assert_eq!(
+            reverse[0]["from"]["modifiers"]["mandatory"],
+            json!(["right_command", "shift"])
+        );
+    }
+
+    fn shell_binding(key: &str, commands: &[&str]) -> Binding {
+        Binding {
+            key: key.to_string(),
+            actions: commands
+                .iter()
+                .map(|c| Action::Shell {
+                    command: c.to_string(),
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_app_conditions_combine_with_cycle() {
+        let binding = Binding {
+            only_in: vec![
+                "com.jetbrains.intellij".to_string(),
+                "/^com\\.google\\./".to_string(),
+            ],
+            except_in: vec!["com.vmware.fusion".to_string()],
+            ..shell_binding("r", &["a", "b"])
+        };
+
+        let rule = generate_binding_rule(&binding, "rcmdb_r_2", &Settings::default());
+        assert!(rule["description"].as_str().unwrap().ends_with(
+            "(only com.jetbrains.intellij, /^com\\.google\\./; except com.vmware.fusion)"
+        ));
+
+        let conditions = &rule["manipulators"][1]["conditions"];
+        assert_eq!(conditions[0]["type"], "frontmost_application_if");
+        assert_eq!(
+            conditions[0]["bundle_identifiers"],
+            json!(["^com\\.jetbrains\\.intellij$", "^com\\.google\\."])
+        );
+        assert_eq!(conditions[1]["type"], "frontmost_application_unless");
+        assert_eq!(conditions[1]["bundle_identifiers"], json!(["^com\\.vmware\\.fusion$"]));
+        assert_eq!(conditions[2]["name"], "rcmdb_r_2_cycle");
+        assert_eq!(conditions[2]["value"], 1);
+    }
+
+    #[test]
+    fn test_modified_actions_come_first() {
+        let binding = Binding {
+            modified: vec![ModifiedActions {
+                modifiers: vec![ExtraModifier::Shift],
+                actions: shell_binding("s", &["new-1", "new-2"]).actions,
+            }],
+            ..shell_binding("s", &["slack"])
+        };
+
+        let rule = generate_binding_rule(&binding, "rcmdb_s", &Settings::default());
+        let manipulators = rule["manipulators"].as_array().unwrap();
+        assert_eq!(manipulators.len(), 4);
+
+        for shifted in &manipulators[..3] {
+            assert_eq!(
+                shifted["from"]["modifiers"]["mandatory"],
+                json!(["right_command", "shift"])
+            );
+        }
+        assert_eq!(manipulators[0]["to"][0]["shell_command"], "new-1");
+        assert_eq!(manipulators[1]["conditions"][0]["name"], "rcmdb_s_shift_cycle");
+        assert_eq!(manipulators[1]["to"][1]["set_variable"]["value"], 2);
+
+        let plain = &manipulators[3];
+        assert_eq!(plain["from"]["modifiers"]["mandatory"], json!(["right_command"]));
+        assert_eq!(plain["to"][0]["shell_command"], "slack");
+    }
+
+    #[test]
+    fn test_hyper_anchor_and_caps_lock_rule() {
+        let mut config = Config {
+            bindings: vec![shell_binding("t", &["terminal"])],
+            ..Default::default()
+        };
+        config.settings.anchor_key = AnchorKey::Hyper;
+
+        let rules = generate_rules(&config);
+        assert_eq!(rules.len(), 1, "caps_lock rule is opt-in");
+        assert_eq!(rules[0]["description"], "[rcmdb] hyper+t");
+        assert_eq!(
+            rules[0]["manipulators"][0]["from"]["modifiers"]["mandatory"],
+            json!(["command", "control", "option", "shift"])
+        );
+
+        config.settings.caps_lock_hyper = true;
+        let rules = generate_rules(&config);
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0]["description"], "[rcmdb] caps_lock → hyper");
+        assert_eq!(rules[0]["manipulators"][0]["from"]["key_code"], "caps_lock");
+
+        config.settings.anchor_key = AnchorKey::RightControl;
+        assert_eq!(generate_rules(&config).len(), 1, "caps_lock rule needs the Hyper anchor");
+    }
+
+    #[test]
+    fn test_anchor_tap_rule() {
+        let mut config = Config {
+            bindings: vec![shell_binding("t", &["terminal"])],
+            ..Default::default()
+        };
+        config.settings.anchor_tap_action = Some(Action::Keystroke {
+            key_code: "spacebar".to_string(),
+            modifiers: vec![KeyModifier::Command],
+        });
+
+        let rules = generate_rules(&config);
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0]["description"], "[rcmdb] rcmd tap");
+        assert!(is_rcmdb_rule(&rules[0]));
+        let tap = &rules[0]["manipulators"][0];
+        assert_eq!(tap["from"]["key_code"], "right_command");
+        assert_eq!(tap["to"], json!([{"key_code": "right_command"}]));
+        assert_eq!(
+            tap["to_if_alone"],
+            json!([{"key_code": "spacebar", "modifiers": ["command"]}])
+        );
+
+        // Hyper taps caps_lock, through the caps_lock rule
+        config.settings.anchor_key = AnchorKey::Hyper;
+        assert_eq!(generate_rules(&config).len(), 1, "Hyper has no key of its own");
+        config.settings.caps_lock_hyper = true;
+        let rules = generate_rules(&config);
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0]["manipulators"][0]["from"]["key_code"], "caps_lock");
+        assert_eq!(rules[0]["manipulators"][0]["to_if_alone"][0]["key_code"], "spacebar");
+
+        // Custom anchors need a single side-specific modifier
+        config.settings.anchor_key = AnchorKey::Custom(vec!["left_option".to_string()]);
+        let rules = generate_rules(&config);
+        assert_eq!(rules[0]["manipulators"][0]["from"]["key_code"], "left_option");
+        config.settings.anchor_key =
+            AnchorKey::Custom(vec!["command".to_string(), "option".to_string()]);
+        assert_eq!(generate_rules(&config).len(), 1);
+    }
+
+    #[test]
+    fn test_leader_sequence_rule() {
+        let config = Config {
+            bindings: vec![
+                shell_binding("t", &["terminal"]),
+                Binding {
+                    sequence: Some("p".to_string()),
+                    ..shell_binding("g", &["pulls"])
+                },
+                Binding {
+                    sequence: Some("i".to_string()),
+                    ..shell_binding("g", &["issues-1", "issues-2"])
+                },
+            ],
+            ..Default::default()
+        };
+
+        let rules = generate_rules(&config);
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0]["description"], "[rcmdb] rcmd+g (leader): p, i");
+        assert_eq!(rules[1]["description"], "[rcmdb] rcmd+t");
+
+        let manipulators = rules[0]["manipulators"].as_array().unwrap();
+        // trigger + 1 single + 3 reverse + 3 cycling + modifier passthroughs + catch-all
+        assert_eq!(manipulators.len(), 9 + MODIFIER_KEY_CODES.len());
+
+        let trigger = &manipulators[0];
+        assert_eq!(trigger["from"]["key_code"], "g");
+        assert_eq!(trigger["to"][0]["set_variable"]["name"], "rcmdb_leader_g");
+        assert_eq!(trigger["to"][0]["set_variable"]["value"], 1);
+        assert_eq!(
+            trigger["to_delayed_action"]["to_if_invoked"][0]["set_variable"]["value"],
+            0
+        );
+        assert_eq!(trigger["parameters"]["basic.to_delayed_action_delay_milliseconds"], 1000);
+
+        let pulls = &manipulators[1];
+        assert_eq!(pulls["from"]["key_code"], "p");
+        assert!(pulls["from"]["modifiers"].get("mandatory").is_none());
+        assert_eq!(pulls["conditions"][0]["name"], "rcmdb_leader_g");
+        assert_eq!(pulls["to"][1]["set_variable"]["name"], "rcmdb_leader_g");
+        assert_eq!(pulls["to"][1]["set_variable"]["value"], 0);
+
+        let issues_back = &manipulators[2];
+        assert_eq!(issues_back["from"]["modifiers"]["mandatory"], json!(["shift"]));
+        assert_eq!(issues_back["conditions"][1]["name"], "rcmdb_g_i_cycle");
+
+        let issues = &manipulators[5];
+        assert_eq!(issues["to"][0]["shell_command"], "issues-1");
+        assert_eq!(issues["conditions"][1]["name"], "rcmdb_g_i_cycle");
+        assert_eq!(issues["to"][2]["set_variable"]["name"], "rcmdb_leader_g");
+
+        // Shift must not disarm the leader, or shift+i could never reach issues_back
+        let shift = &manipulators[8];
+        assert_eq!(shift["from"]["key_code"], "left_shift");
+        assert_eq!(shift["to"], json!([{ "key_code": "left_shift" }]));
+        assert_eq!(shift["conditions"][0]["name"], "rcmdb_leader_g");
+
+        let catch_all = manipulators.last().unwrap();
+        assert_eq!(catch_all["from"]["any"], "key_code");
+        assert_eq!(catch_all["to"][0]["set_variable"]["value"], 0);
+        let disarming: Vec<&Value> = manipulators
+            .iter()
+            .filter(|m| m["from"]["key_code"].is_string())
+            .filter(|m| m["to"].to_string().contains("\"value\":0"))
+            .map(|m| &m["from"]["key_code"])
+            .collect();
+        assert!(
+            disarming.iter().all(|k| !MODIFIER_KEY_CODES.contains(&k.as_str().unwrap())),
+            "{:?}",
+            disarming
+        );
+    }
+
+    #[test]
+    fn test_conditional_bindings_come_first() {
+        let config = Config {
+            bindings: vec![
+                shell_binding("r", &["default"]),
+                Binding {
+                    only_in: vec!["com.jetbrains.intellij".to_string()],
+                    ..shell_binding("r", &["ide-1", "ide-2"])
+                },
+                Binding {
+                    only_in: vec!["com.google.Chrome".to_string()],
+                    ..shell_binding("r", &["web-1", "web-2"])
+                },
+            ],
+            ..Default::default()
+        };
+
+        let rules = generate_rules(&config);
+        let commands: Vec<&Value> = rules
+            .iter()
+            .map(|r| &r["manipulators"][0]["to"][0]["shell_command"])
+            .collect();
+        // A fresh reverse cycle comes first and starts at the last action
+        assert_eq!(commands, vec!["ide-2", "web-2", "default"]);
+        assert_eq!(rules[0]["manipulators"][0]["conditions"][1]["name"], "rcmdb_r_cycle");
+        assert_eq!(rules[1]["manipulators"][0]["conditions"][1]["name"], "rcmdb_r_2_cycle");
+        assert!(rules[2]["manipulators"][0].get("conditions").is_none());
     }
 
     #[test]
@@ -312,9 +1943,10 @@ mod tests {
         let action = Action::App {
             target: "Terminal".to_string(),
             bundle_id: Some("com.apple.Terminal".to_string()),
+            toggle: false,
         };
         let cmd = action_to_karabiner(&action, &Browser::Firefox, CenterMouseMode::Always);
-        let shell_cmd = cmd["shell_command"].as_str().unwrap();
+        let shell_cmd = cmd[0]["shell_command"].as_str().unwrap();
 
         assert!(shell_cmd.contains("$HOME"), "must contain literal $HOME, not an absolute path");
         assert!(!shell_cmd.contains("/Users/"), "must not bake absolute user path at save time");
@@ -329,25 +1961,134 @@ mod tests {
         let action = Action::App {
             target: "Terminal".to_string(),
             bundle_id: Some("com.apple.Terminal".to_string()),
+            toggle: false,
         };
         let cmd = action_to_karabiner(&action, &Browser::Firefox, CenterMouseMode::MultiMonitorOnly);
-        let shell_cmd = cmd["shell_command"].as_str().unwrap();
+        let shell_cmd = cmd[0]["shell_command"].as_str().unwrap();
         assert!(shell_cmd.contains("center-mouse.sh"), "must invoke center-mouse.sh");
         assert!(shell_cmd.contains("'multi_monitor_only'"), "must pass multi_monitor_only mode arg");
     }
 
+    fn profiles_doc() -> Value {
+        json!({
+            "profiles": [
+                { "name": "Work", "complex_modifications": { "rules": [
+                    { "description": "[rcmdb] rcmd+x", "manipulators": [] }
+                ] } },
+                { "name": "Gaming", "complex_modifications": { "rules": [
+                    { "description": "Mine", "manipulators": [] }
+                ] } }
+            ]
+        })
+    }
+
+    fn rule_descriptions(doc: &Value, profile: usize) -> Vec<String> {
+        doc["profiles"][profile]["complex_modifications"]["rules"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["description"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_build_writes_first_profile_by_default() {
+        let mut config = Config::default();
+        config.bindings.push(Binding {
+            key: "t".to_string(),
+            ..Default::default()
+        });
+
+        let doc = build_karabiner_config(&profiles_doc(), &config).unwrap();
+        assert_eq!(rule_descriptions(&doc, 0), vec!["[rcmdb] rcmd+t"]);
+        assert_eq!(rule_descriptions(&doc, 1), vec!["Mine"]);
+    }
+
+    #[test]
+    fn test_build_only_touches_named_profiles() {
+        let mut config = Config::default();
+        config.settings.profiles = vec!["Gaming".to_string()];
+        config.bindings.push(Binding {
+            key: "t".to_string(),
+            ..Default::default()
+        });
+
+        let doc = build_karabiner_config(&profiles_doc(), &config).unwrap();
+        // Work keeps its stale rule: it is not a target profile
+        assert_eq!(rule_descriptions(&doc, 0), vec!["[rcmdb] rcmd+x"]);
+        assert_eq!(rule_descriptions(&doc, 1), vec!["Mine", "[rcmdb] rcmd+t"]);
+    }
+
+    #[test]
+    fn test_build_fails_for_unknown_profile() {
+        let mut config = Config::default();
+        config.settings.profiles = vec!["Travel".to_string()];
+
+        let err = build_karabiner_config(&profiles_doc(), &config).unwrap_err();
+        assert!(err.to_string().contains("'Travel' not found"));
+        assert!(err.to_string().contains("Work, Gaming"));
+    }
+
     #[test]
     fn test_url_action_uses_runtime_home_path() {
         let action = Action::Url {
             target: "https://example.com".to_string(),
             match_type: crate::config::UrlMatchType::Domain,
             browser: None,
+            profile: None,
+            options: Default::default(),
         };
         let cmd = action_to_karabiner(&action, &Browser::Firefox, CenterMouseMode::Off);
-        let shell_cmd = cmd["shell_command"].as_str().unwrap();
+        let shell_cmd = cmd[0]["shell_command"].as_str().unwrap();
 
         assert!(shell_cmd.contains("$HOME"), "must contain literal $HOME, not an absolute path");
         assert!(!shell_cmd.contains("/Users/"), "must not bake absolute user path at save time");
         assert!(shell_cmd.contains("\"$HOME"), "must double-quote $HOME for shell expansion");
     }
+
+    fn command_words(action: &Action, center_mouse: CenterMouseMode) -> Vec<String> {
+        use crate::karabiner::quote::split_shell_words;
+        let cmd = action_to_karabiner(action, &Browser::Safari, center_mouse);
+        split_shell_words(cmd[0]["shell_command"].as_str().unwrap()).unwrap()
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_targets_stay_one_argument(target in proptest::prelude::any::<String>()) {
+            let app = Action::App {
+                target: target.clone(),
+                bundle_id: None,
+                toggle: false,
+            };
+            proptest::prop_assert_eq!(
+                command_words(&app, CenterMouseMode::Off),
+                vec!["open".to_string(), "-a".to_string(), target.clone()]
+            );
+
+            // An empty bundle ID falls back to the app name
+            if !target.is_empty() {
+                let app = Action::App {
+                    target: "App".to_string(),
+                    bundle_id: Some(target.clone()),
+                    toggle: false,
+                };
+                proptest::prop_assert_eq!(
+                    command_words(&app, CenterMouseMode::Off),
+                    vec!["open".to_string(), "-b".to_string(), target.clone()]
+                );
+            }
+
+            let url = Action::Url {
+                target: target.clone(),
+                match_type: crate::config::UrlMatchType::Exact,
+                browser: None,
+                profile: None,
+                options: Default::default(),
+            };
+            let words = command_words(&url, CenterMouseMode::Off);
+            proptest::prop_assert_eq!(words.len(), 8);
+            proptest::prop_assert_eq!(&words[1..3], &["focus-url", "--match-type"]);
+            proptest::prop_assert_eq!(&words[6..], &["--".to_string(), target]);
+        }
+    }
 }
@@ -0,0 +1,912 @@
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::app_discovery::DiscoveredApp;
+use crate::commands::focus_url;
+use crate::config::{
+    Action, AnchorKey, Binding, Browser, Config, ExtraModifier, KeyModifier, ModifiedActions,
+    UrlMatchOptions, UrlMatchType,
+};
+use crate::karabiner::generator::{is_rcmdb_rule, VAR_PREFIX};
+use crate::karabiner::quote::split_shell_words;
+use crate::keycodes::is_valid_key;
+
+/// A rule that could not be converted into bindings
+#[derive(Debug, Clone)]
+pub struct SkippedRule {
+    pub description: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    pub bindings: Vec<Binding>,
+    pub skipped: Vec<SkippedRule>,
+}
+
+/// One manipulator recognised as something our generator could have produced
+struct ParsedManipulator {
+    key: String,
+    /// Cycle variable name, the value it must have and the value it is set to,
+    /// for cycling manipulators
+    cycle: Option<(String, i64, i64)>,
+    only_in: Vec<String>,
+    except_in: Vec<String>,
+    /// Modifiers held besides the anchor
+    modifiers: Vec<ExtraModifier>,
+    action: Action,
+    /// Events of to_if_held_down
+    hold_actions: Vec<Action>,
+}
+
+/// Manipulators that make up one action list: a single action, or the steps of a cycle
+struct ActionGroup {
+    /// Cycle variable (None for a single action)
+    var: Option<String>,
+    binding: Binding,
+    modifiers: Vec<ExtraModifier>,
+    /// (cycle value, value set next, action)
+    steps: Vec<(i64, i64, Action)>,
+}
+
+impl ActionGroup {
+    /// Steps in cycle order; cycle values must run 0, 1, 2, ... Cycles generated by
+    /// rcmdb hold one past the last action run, so their final value n repeats the
+    /// first action and, like value 0, sets the variable to 1. That wrap step is
+    /// dropped; a hand-written cycle's last step sets it back to 0 instead.
+    fn actions(&mut self) -> Result<Vec<Action>, String> {
+        self.steps.sort_by_key(|(value, _, _)| *value);
+        let in_order = self
+            .steps
+            .iter()
+            .enumerate()
+            .all(|(i, (value, _, _))| *value == i as i64);
+        if !in_order {
+            return Err(format!(
+                "cycle for '{}' has gaps in its variable values",
+                self.binding.key
+            ));
+        }
+        let wraps = |step: Option<&(i64, i64, Action)>| step.is_some_and(|(_, next, _)| *next == 1);
+        if self.steps.len() > 2 && wraps(self.steps.first()) && wraps(self.steps.last()) {
+            self.steps.pop();
+        }
+        Ok(std::mem::take(&mut self.steps)
+            .into_iter()
+            .map(|(_, _, action)| action)
+            .collect())
+    }
+}
+
+/// Convert hand-written complex_modifications rules of one profile into bindings.
+/// Our own [rcmdb] rules are ignored: they are already described by config.toml.
+pub fn import_rules(
+    kara_config: &Value,
+    profile: usize,
+    anchor_key: &AnchorKey,
+    apps: &[DiscoveredApp],
+) -> ImportReport {
+    let rules = kara_config
+        .get("profiles")
+        .and_then(|p| p.get(profile))
+        .and_then(|p| p.get("complex_modifications"))
+        .and_then(|c| c.get("rules"))
+        .and_then(|r| r.as_array())
+        .map(|r| r.as_slice())
+        .unwrap_or(&[]);
+
+    let mut report = ImportReport::default();
+    for rule in rules.iter().filter(|r| !is_rcmdb_rule(r)) {
+        let description = rule
+            .get("description")
+            .and_then(|d| d.as_str())
+            .unwrap_or("(no description)")
+            .to_string();
+
+        match import_rule(rule, &description, anchor_key, apps) {
+            Ok(bindings) => report.bindings.extend(bindings),
+            Err(reason) => report.skipped.push(SkippedRule {
+                description,
+                reason,
+            }),
+        }
+    }
+    report
+}
+
+/// Append imported bindings to the config, skipping keys (and leader sequences) that
+/// are already bound under the same app conditions. Existing bindings keep their
+/// order. Returns the keys that were skipped.
+pub fn merge_imported(config: &mut Config, bindings: Vec<Binding>) -> Vec<String> {
+    let slot = |b: &Binding| {
+        (
+            b.key.clone(),
+            b.sequence.clone(),
+            b.only_in.clone(),
+            b.except_in.clone(),
+        )
+    };
+    let mut existing: HashSet<_> = config.bindings.iter().map(slot).collect();
+    let mut conflicts = Vec::new();
+
+    for binding in bindings {
+        if !existing.insert(slot(&binding)) {
+            conflicts.push(binding.key);
+            continue;
+        }
+        config.bindings.push(binding);
+    }
+    conflicts
+}
+
+fn import_rule(
+    rule: &Value,
+    description: &str,
+    anchor_key: &AnchorKey,
+    apps: &[DiscoveredApp],
+) -> Result<Vec<Binding>, String> {
+    let manipulators = rule
+        .get("manipulators")
+        .and_then(|m| m.as_array())
+        .filter(|m| !m.is_empty())
+        .ok_or("no manipulators")?;
+
+    let parsed = manipulators
+        .iter()
+        .map(|m| parse_manipulator(m, anchor_key, apps))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Group manipulators: single actions stand alone,
+    // cycling manipulators are grouped by their variable and ordered by its value
+    let mut groups: Vec<ActionGroup> = Vec::new();
+    for m in parsed {
+        let var = m.cycle.as_ref().map(|(var, _, _)| var.clone());
+        let (value, next) = m.cycle.map(|(_, value, next)| (value, next)).unwrap_or((0, 0));
+        let step = (value, next, m.action);
+        let same_group =
+            |g: &&mut ActionGroup| var.is_some() && g.var == var && g.modifiers == m.modifiers;
+        match groups.iter_mut().find(same_group) {
+            Some(group) => group.steps.push(step),
+            None => groups.push(ActionGroup {
+                var,
+                binding: Binding {
+                    key: m.key,
+                    only_in: m.only_in,
+                    except_in: m.except_in,
+                    hold_actions: m.hold_actions,
+                    ..Default::default()
+                },
+                modifiers: m.modifiers,
+                steps: vec![step],
+            }),
+        }
+    }
+
+    // Plain groups become bindings; modified groups attach to the plain binding
+    // with the same key and app conditions. A shift group stepping the plain cycle's
+    // variable is its reverse cycle, which the generator adds back by itself.
+    let (modified, plain): (Vec<ActionGroup>, Vec<ActionGroup>) =
+        groups.into_iter().partition(|g| !g.modifiers.is_empty());
+    let plain_vars: Vec<&String> = plain.iter().filter_map(|g| g.var.as_ref()).collect();
+    let modified: Vec<ActionGroup> = modified
+        .into_iter()
+        .filter(|g| {
+            let reverse = g.modifiers == [ExtraModifier::Shift]
+                && g.var.as_ref().is_some_and(|var| plain_vars.contains(&var));
+            !reverse
+        })
+        .collect();
+    let mut bindings: Vec<Binding> = Vec::new();
+    for mut group in plain {
+        let actions = group.actions()?;
+        bindings.push(Binding {
+            actions,
+            ..group.binding
+        });
+    }
+    for mut group in modified {
+        let actions = group.actions()?;
+        let owner = bindings.iter_mut().find(|b| {
+            b.key == group.binding.key
+                && b.only_in == group.binding.only_in
+                && b.except_in == group.binding.except_in
+        });
+        let Some(owner) = owner else {
+            return Err(format!(
+                "'{}' has modified actions but no plain binding",
+                group.binding.key
+            ));
+        };
+        owner.modified.push(ModifiedActions {
+            modifiers: group.modifiers,
+            actions,
+        });
+    }
+
+    let mut seen = HashSet::new();
+    if let Some(b) = bindings
+        .iter()
+        .find(|b| !seen.insert((b.key.clone(), b.only_in.clone(), b.except_in.clone())))
+    {
+        return Err(format!("key '{}' is bound more than once", b.key));
+    }
+
+    let single = bindings.len() == 1;
+    for binding in &mut bindings {
+        binding.description = if single {
+            description.to_string()
+        } else {
+            format!("{} ({})", description, binding.key)
+        };
+    }
+    Ok(bindings)
+}
+
+fn parse_manipulator(
+    manipulator: &Value,
+    anchor_key: &AnchorKey,
+    apps: &[DiscoveredApp],
+) -> Result<ParsedManipulator, String> {
+    if manipulator.get("type").and_then(|t| t.as_str()) != Some("basic") {
+        return Err("not a basic manipulator".to_string());
+    }
+
+    let from = manipulator.get("from").ok_or("manipulator has no 'from'")?;
+    let key = from
+        .get("key_code")
+        .and_then(|k| k.as_str())
+        .ok_or("'from' is not a key_code event")?;
+    if !is_valid_key(key) {
+        return Err(format!("unsupported key '{}'", key));
+    }
+
+    let mandatory: Vec<&str> = from
+        .get("modifiers")
+        .and_then(|m| m.get("mandatory"))
+        .and_then(|m| m.as_array())
+        .map(|m| m.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+    // Every anchor modifier must be present; anything left over must be an extra modifier
+    let anchor = anchor_key.karabiner_modifiers();
+    let mut extra = mandatory;
+    let has_anchor = anchor.iter().all(|a| match extra.iter().position(|m| m == a) {
+        Some(idx) => {
+            extra.remove(idx);
+            true
+        }
+        None => false,
+    });
+    let modifiers = extra
+        .iter()
+        .map(|m| ExtraModifier::from_karabiner_modifier(m))
+        .collect::<Option<Vec<_>>>()
+        .filter(|_| has_anchor)
+        .ok_or_else(|| {
+            format!(
+                "'{}' is not triggered by {} (plus shift/option/control)",
+                key,
+                anchor.join("+")
+            )
+        })?;
+
+    // A tap-vs-hold manipulator keeps its tap events in to_if_alone
+    let events = |field: &str| {
+        manipulator
+            .get(field)
+            .and_then(|t| t.as_array())
+            .map(|t| t.as_slice())
+            .unwrap_or(&[])
+    };
+    let to = Some(events("to"))
+        .filter(|to| !to.is_empty())
+        .unwrap_or_else(|| events("to_if_alone"));
+    let hold_actions = events("to_if_held_down")
+        .iter()
+        .map(|e| {
+            let mut event = e.clone();
+            if let Some(event) = event.as_object_mut() {
+                event.remove("repeat");
+            }
+            parse_to_event(&event, apps)
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| format!("'{}' has hold events rcmdb cannot express", key))?;
+    let action = to
+        .first()
+        .and_then(|e| parse_to_event(e, apps))
+        .ok_or_else(|| format!("'{}' does not run a shell command or send a key", key))?;
+
+    let conditions = manipulator
+        .get("conditions")
+        .and_then(|c| c.as_array())
+        .map(|c| c.as_slice())
+        .unwrap_or(&[]);
+
+    // Frontmost-app conditions become only_in / except_in, anything else must be a cycle step
+    let mut only_in = Vec::new();
+    let mut except_in = Vec::new();
+    let mut other_conditions = Vec::new();
+    for condition in conditions {
+        match condition.get("type").and_then(|t| t.as_str()) {
+            Some("frontmost_application_if") => only_in.extend(parse_app_condition(condition, key)?),
+            Some("frontmost_application_unless") => {
+                except_in.extend(parse_app_condition(condition, key)?)
+            }
+            _ => other_conditions.push(condition),
+        }
+    }
+
+    let cycle = match (other_conditions.as_slice(), &to[1..]) {
+        ([], []) => None,
+        ([condition], [set_variable]) => Some(parse_cycle_step(condition, set_variable, key)?),
+        _ => return Err(format!("'{}' has conditions or events rcmdb cannot express", key)),
+    };
+
+    Ok(ParsedManipulator {
+        key: key.to_string(),
+        cycle,
+        only_in,
+        except_in,
+        modifiers,
+        action,
+        hold_actions,
+    })
+}
+
+/// Convert a frontmost_application condition's bundle_identifiers back into
+/// app list entries: exact-match patterns become bundle IDs, anything else /regex/
+fn parse_app_condition(condition: &Value, key: &str) -> Result<Vec<String>, String> {
+    let field_count = condition.as_object().map(|o| o.len()).unwrap_or(0);
+    let patterns = condition
+        .get("bundle_identifiers")
+        .and_then(|b| b.as_array())
+        .filter(|_| field_count == 2)
+        .ok_or_else(|| format!("'{}' matches apps by something other than bundle ID", key))?;
+
+    Ok(patterns
+        .iter()
+        .filter_map(|p| p.as_str())
+        .map(|p| unescape_bundle_id_pattern(p).unwrap_or_else(|| format!("/{}/", p)))
+        .collect())
+}
+
+/// Inverse of `bundle_id_pattern`: "^com\.apple\.Terminal$" -> "com.apple.Terminal"
+fn unescape_bundle_id_pattern(pattern: &str) -> Option<String> {
+    let inner = pattern.strip_prefix('^')?.strip_suffix('$')?;
+    let mut out = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.push(chars.next()?),
+            '.' | '+' | '*' | '?' | '(' | ')' | '|' | '[' | ']' | '{' | '}' | '^' | '$' => {
+                return None
+            }
+            _ => out.push(c),
+        }
+    }
+    Some(out)
+}
+
+/// Recognise `variable_if rcmdb_<key>[_N][_<modifiers>]_cycle == i` plus the matching `set_variable`.
+/// Returns the variable name, which identifies the binding the step belongs to.
+fn parse_cycle_step(
+    condition: &Value,
+    set_variable: &Value,
+    key: &str,
+) -> Result<(String, i64, i64), String> {
+    let base = format!("{}{}", VAR_PREFIX, key);
+    let unsupported = || format!("'{}' uses a condition other than {}_cycle", key, base);
+
+    if condition.get("type").and_then(|t| t.as_str()) != Some("variable_if") {
+        return Err(unsupported());
+    }
+    let var = condition
+        .get("name")
+        .and_then(|n| n.as_str())
+        .ok_or_else(unsupported)?;
+    let slot = var
+        .strip_prefix(base.as_str())
+        .and_then(|rest| rest.strip_suffix("_cycle"))
+        .ok_or_else(unsupported)?;
+    // "", "_2", "_shift", "_2_shift_option", ...
+    let valid_slot = slot.is_empty()
+        || slot.strip_prefix('_').is_some_and(|n| {
+            !n.is_empty()
+                && n.chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        });
+    if !valid_slot {
+        return Err(unsupported());
+    }
+    let value = condition
+        .get("value")
+        .and_then(|v| v.as_i64())
+        .ok_or_else(unsupported)?;
+
+    let set = set_variable.get("set_variable").ok_or_else(unsupported)?;
+    if set.get("name").and_then(|n| n.as_str()) != Some(var) {
+        return Err(unsupported());
+    }
+    let next = set
+        .get("value")
+        .and_then(|v| v.as_i64())
+        .ok_or_else(unsupported)?;
+
+    Ok((var.to_string(), value, next))
+}
+
+/// A `to` event our generator could have produced: a shell_command, or a key_code
+/// with optional modifiers
+fn parse_to_event(event: &Value, apps: &[DiscoveredApp]) -> Option<Action> {
+    let event = event.as_object()?;
+    if let Some(command) = event.get("shell_command") {
+        return Some(parse_shell_command(command.as_str()?, apps)).filter(|_| event.len() == 1);
+    }
+
+    let key_code = event.get("key_code")?.as_str()?;
+    let modifiers = match event.get("modifiers") {
+        None => Vec::new(),
+        Some(Value::String(m)) => vec![KeyModifier::from_karabiner_modifier(m)?],
+        Some(m) => m
+            .as_array()?
+            .iter()
+            .map(|m| KeyModifier::from_karabiner_modifier(m.as_str()?))
+            .collect::<Option<Vec<_>>>()?,
+    };
+    let expected_len = if event.contains_key("modifiers") { 2 } else { 1 };
+    if event.len() != expected_len || !is_valid_key(key_code) {
+        return None;
+    }
+    Some(Action::Keystroke {
+        key_code: key_code.to_string(),
+        modifiers,
+    })
+}
+
+/// Map a shell_command back to the action that would generate it.
+/// Anything unrecognised is kept verbatim as a Shell action.
+fn parse_shell_command(command: &str, apps: &[DiscoveredApp]) -> Action {
+    let shell = || Action::Shell {
+        command: command.to_string(),
+    };
+
+    // Strip the center-mouse suffix: `open -b <id> && "<dir>/center-mouse.sh" '<id>' '<mode>'`
+    let primary = match command.split_once(" && ") {
+        Some((head, tail)) if tail.contains("center-mouse.sh") => head,
+        Some(_) => return shell(),
+        None => command,
+    };
+
+    let Some(words) = split_shell_words(primary) else {
+        return shell();
+    };
+    let words: Vec<&str> = words.iter().map(|w| w.as_str()).collect();
+
+    match words.as_slice() {
+        ["open", "-b", bundle_id] => {
+            let target = apps
+                .iter()
+                .find(|app| app.bundle_id == *bundle_id)
+                .map(|app| app.name.clone())
+                .unwrap_or_else(|| {
+                    bundle_id.rsplit('.').next().unwrap_or(bundle_id).to_string()
+                });
+            Action::App {
+                target,
+                bundle_id: Some(bundle_id.to_string()),
+                toggle: false,
+            }
+        }
+        ["open", "-a", name] => Action::App {
+            target: name.to_string(),
+            bundle_id: apps
+                .iter()
+                .find(|app| app.name == *name)
+                .map(|app| app.bundle_id.clone()),
+            toggle: false,
+        },
+        // Read with the same parser the binary uses, so flags can't drift
+        [launcher, "focus-url", args @ ..] if launcher.ends_with("/rcmdb") => {
+            match focus_url::parse_args(args) {
+                Some(args) => Action::Url {
+                    options: args.options(),
+                    target: args.url,
+                    match_type: args.match_type,
+                    browser: Some(args.browser),
+                    profile: args.profile,
+                },
+                _ => shell(),
+            }
+        }
+        // url-focus.sh from older versions
+        [script, url, match_type, browser] if script.ends_with("/url-focus.sh") => {
+            let match_type = UrlMatchType::all()
+                .iter()
+                .find(|t| t.as_str() == *match_type);
+            let browser = Browser::all().iter().find(|b| b.as_str() == *browser);
+            match (match_type, browser) {
+                (Some(match_type), Some(browser)) => Action::Url {
+                    target: url.to_string(),
+                    match_type: match_type.clone(),
+                    browser: Some(browser.clone()),
+                    profile: None,
+                    options: UrlMatchOptions::default(),
+                },
+                _ => shell(),
+            }
+        }
+        _ => shell(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CenterMouseMode;
+    use crate::karabiner::generator::generate_rules;
+    use serde_json::json;
+
+    fn doc(rules: Value) -> Value {
+        json!({ "profiles": [{ "complex_modifications": { "rules": rules } }] })
+    }
+
+    fn binding(key: &str, actions: Vec<Action>) -> Binding {
+        Binding {
+            key: key.to_string(),
+            description: "Imported".to_string(),
+            actions,
+            ..Default::default()
+        }
+    }
+
+    /// Rules produced by our own generator (renamed so they are not [rcmdb]-owned)
+    /// must import back to the same actions
+    #[test]
+    fn test_generated_rules_round_trip() {
+        let mut config = Config::default();
+        config.settings.center_mouse = CenterMouseMode::Always;
+        config.bindings = vec![
+            binding(
+                "t",
+                vec![
+                    Action::App {
+                        target: "Terminal".to_string(),
+                        bundle_id: Some("com.apple.Terminal".to_string()),
+                        toggle: false,
+                    },
+                    Action::App {
+                        target: "Visual Studio Code".to_string(),
+                        bundle_id: None,
+                        toggle: false,
+                    },
+                ],
+            ),
+            binding(
+                "g",
+                vec![Action::Url {
+                    target: "https://github.com/org/*".to_string(),
+                    match_type: UrlMatchType::Glob,
+                    browser: Some(Browser::Chrome),
+                    profile: Some("Work".to_string()),
+                    options: UrlMatchOptions {
+                        ignore_query: true,
+                        ..Default::default()
+                    },
+                }],
+            ),
+            binding(
+                "h",
+                vec![Action::Shell {
+                    command: "open ~ && echo done".to_string(),
+                }],
+            ),
+            binding(
+                "k",
+                vec![Action::Keystroke {
+                    key_code: "left_arrow".to_string(),
+                    modifiers: vec![KeyModifier::Option, KeyModifier::Control],
+                }],
+            ),
+        ];
+
+        let rules: Vec<Value> = generate_rules(&config)
+            .into_iter()
+            .map(|mut rule| {
+                rule["description"] = json!("Imported");
+                rule
+            })
+            .collect();
+        let apps = vec![DiscoveredApp {
+            name: "Terminal".to_string(),
+            bundle_id: "com.apple.Terminal".to_string(),
+            last_component: "Terminal".to_string(),
+            last_seen: 0,
+        }];
+
+        let report = import_rules(&doc(json!(rules)), 0, &AnchorKey::RightCommand, &apps);
+        assert!(report.skipped.is_empty(), "{:?}", report.skipped);
+
+        let expected: Vec<String> = config
+            .bindings
+            .iter()
+            .map(|b| format!("{:?}", b.actions))
+            .collect();
+        let imported: Vec<String> = report
+            .bindings
+            .iter()
+            .map(|b| format!("{:?}", b.actions))
+            .collect();
+        assert_eq!(imported, expected);
+        // The shift manipulators of the "t" cycle are its reverse, not modified actions
+        assert!(report.bindings.iter().all(|b| b.modified.is_empty()));
+    }
+
+    #[test]
+    fn test_multi_key_rule_splits_into_bindings() {
+        let rule = json!({
+            "description": "Launchers",
+            "manipulators": [
+                {
+                    "type": "basic",
+                    "from": { "key_code": "s", "modifiers": { "mandatory": ["right_command"] } },
+                    "to": [{ "shell_command": "open -a 'Slack'" }]
+                },
+                {
+                    "type": "basic",
+                    "from": { "key_code": "n", "modifiers": { "mandatory": ["right_command"] } },
+                    "to": [{ "shell_command": "open -b com.apple.Notes" }]
+                }
+            ]
+        });
+        let report = import_rules(&doc(json!([rule])), 0, &AnchorKey::RightCommand, &[]);
+        assert_eq!(report.bindings.len(), 2);
+        assert_eq!(report.bindings[0].description, "Launchers (s)");
+        assert!(matches!(
+            &report.bindings[1].actions[0],
+            Action::App { target, bundle_id: Some(id), .. } if target == "Notes" && id == "com.apple.Notes"
+        ));
+    }
+
+    #[test]
+    fn test_hand_written_cycle_back_to_the_first_action() {
+        // Slack, Mail, Slack: the last step repeats the first but sets the variable to 0
+        let step = |value: i64, next: i64, app: &str| {
+            json!({
+                "type": "basic",
+                "from": { "key_code": "m", "modifiers": { "mandatory": ["right_command"] } },
+                "to": [
+                    { "shell_command": format!("open -a '{}'", app) },
+                    { "set_variable": { "name": "rcmdb_m_cycle", "value": next } }
+                ],
+                "conditions": [{ "type": "variable_if", "name": "rcmdb_m_cycle", "value": value }]
+            })
+        };
+        let rule = json!({
+            "description": "Chat",
+            "manipulators": [step(0, 1, "Slack"), step(1, 2, "Mail"), step(2, 0, "Slack")]
+        });
+        let report = import_rules(&doc(json!([rule])), 0, &AnchorKey::RightCommand, &[]);
+        let targets: Vec<&str> = report.bindings[0]
+            .actions
+            .iter()
+            .map(|action| match action {
+                Action::App { target, .. } => target.as_str(),
+                _ => panic!("not an app action"),
+            })
+            .collect();
+        assert_eq!(targets, ["Slack", "Mail", "Slack"]);
+    }
+
+    #[test]
+    fn test_unconvertible_rules_are_reported() {
+        let rules = json!([
+            {
+                "description": "Caps to escape",
+                "manipulators": [{
+                    "type": "basic",
+                    "from": { "key_code": "caps_lock" },
+                    "to": [{ "key_code": "escape" }]
+                }]
+            },
+            {
+                "description": "Other anchor",
+                "manipulators": [{
+                    "type": "basic",
+                    "from": { "key_code": "t", "modifiers": { "mandatory": ["right_option"] } },
+                    "to": [{ "shell_command": "open -a Terminal" }]
+                }]
+            },
+            {
+                "description": "[rcmdb] rcmd+t",
+                "manipulators": []
+            }
+        ]);
+        let report = import_rules(&doc(rules), 0, &AnchorKey::RightCommand, &[]);
+        assert!(report.bindings.is_empty());
+        let skipped: Vec<&str> = report.skipped.iter().map(|s| s.description.as_str()).collect();
+        assert_eq!(skipped, vec!["Caps to escape", "Other anchor"]);
+    }
+
+    #[test]
+    fn test_app_conditions_round_trip() {
+        let config = Config {
+            bindings: vec![
+                Binding {
+                    except_in: vec!["com.vmware.fusion".to_string()],
+                    ..binding("t", vec![Action::Shell { command: "a".to_string() }])
+                },
+                Binding {
+                    only_in: vec![
+                        "com.jetbrains.intellij".to_string(),
+                        "/^com\\.google\\./".to_string(),
+                    ],
+                    ..binding(
+                        "t",
+                        vec![
+                            Action::Shell { command: "b".to_string() },
+                            Action::Shell { command: "c".to_string() },
+                        ],
+                    )
+                },
+            ],
+            ..Default::default()
+        };
+        let rules: Vec<Value> = generate_rules(&config)
+            .into_iter()
+            .map(|mut rule| {
+                rule["description"] = json!("Imported");
+                rule
+            })
+            .collect();
+
+        let report = import_rules(&doc(json!(rules)), 0, &AnchorKey::RightCommand, &[]);
+        assert!(report.skipped.is_empty(), "{:?}", report.skipped);
+        let imported: Vec<(&[String], &[String], usize)> = report
+            .bindings
+            .iter()
+            .map(|b| (b.only_in.as_slice(), b.except_in.as_slice(), b.actions.len()))
+            .collect();
+        assert_eq!(
+            imported,
+            vec![
+                (&[][..], config.bindings[0].except_in.as_slice(), 1),
+                (config.bindings[1].only_in.as_slice(), &[][..], 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_modified_actions_round_trip() {
+        let shell = |c: &str| Action::Shell {
+            command: c.to_string(),
+        };
+        let config = Config {
+            bindings: vec![Binding {
+                modified: vec![ModifiedActions {
+                    modifiers: vec![ExtraModifier::Shift, ExtraModifier::Option],
+                    actions: vec![shell("b"), shell("c")],
+                }],
+                ..binding("s", vec![shell("a")])
+            }],
+            ..Default::default()
+        };
+        let rules: Vec<Value> = generate_rules(&config)
+            .into_iter()
+            .map(|mut rule| {
+                rule["description"] = json!("Imported");
+                rule
+            })
+            .collect();
+
+        let report = import_rules(&doc(json!(rules)), 0, &AnchorKey::RightCommand, &[]);
+        assert!(report.skipped.is_empty(), "{:?}", report.skipped);
+        assert_eq!(report.bindings.len(), 1);
+        let imported = &report.bindings[0];
+        assert_eq!(format!("{:?}", imported.actions), format!("{:?}", vec![shell("a")]));
+        assert_eq!(imported.modified.len(), 1);
+        assert_eq!(imported.modified[0].modifiers_label(), "shift+option");
+        assert_eq!(
+            format!("{:?}", imported.modified[0].actions),
+            format!("{:?}", vec![shell("b"), shell("c")])
+        );
+    }
+
+    #[test]
+    fn test_hold_actions_round_trip() {
+        let shell = |c: &str| Action::Shell {
+            command: c.to_string(),
+        };
+        let new_window = Action::Keystroke {
+            key_code: "n".to_string(),
+            modifiers: vec![KeyModifier::Command],
+        };
+        let config = Config {
+            bindings: vec![Binding {
+                hold_actions: vec![new_window.clone()],
+                ..binding("t", vec![shell("a"), shell("b")])
+            }],
+            ..Default::default()
+        };
+        let rules: Vec<Value> = generate_rules(&config)
+            .into_iter()
+            .map(|mut rule| {
+                rule["description"] = json!("Imported");
+                rule
+            })
+            .collect();
+
+        let report = import_rules(&doc(json!(rules)), 0, &AnchorKey::RightCommand, &[]);
+        assert!(report.skipped.is_empty(), "{:?}", report.skipped);
+        assert_eq!(report.bindings.len(), 1);
+        let imported = &report.bindings[0];
+        assert_eq!(
+            format!("{:?}", imported.actions),
+            format!("{:?}", vec![shell("a"), shell("b")])
+        );
+        assert_eq!(
+            format!("{:?}", imported.hold_actions),
+            format!("{:?}", vec![new_window])
+        );
+    }
+
+    #[test]
+    fn test_url_commands_from_older_versions() {
+        let url = |command: &str| match parse_shell_command(command, &[]) {
+            Action::Url {
+                target,
+                match_type,
+                options,
+                ..
+            } => Some((target, match_type, options)),
+            _ => None,
+        };
+        let script = "\"$HOME/.config/karabiner-rcmd-binder/scripts/url-focus.sh\"";
+        assert_eq!(
+            url(&format!("{} 'https://a.com' 'domain' 'chrome'", script)),
+            Some(("https://a.com".into(), UrlMatchType::Domain, Default::default()))
+        );
+        assert_eq!(
+            url(&format!("{} 'https://a.com/x' 'exact' 'safari' '^x$'", script)),
+            None,
+            "url-focus.sh never took a pattern"
+        );
+
+        let launcher = "\"$HOME/.config/karabiner-rcmd-binder/scripts/rcmdb\"";
+        assert_eq!(
+            url(&format!("{} focus-url --browser 'arc' -- '-x'", launcher)),
+            Some(("-x".into(), UrlMatchType::Exact, Default::default()))
+        );
+        assert!(matches!(
+            parse_shell_command(&format!("{} focus-url --browser 'brave' -- 'a.com'", launcher), &[]),
+            Action::Url { browser: Some(Browser::Custom(name)), .. } if name == "brave"
+        ));
+        assert_eq!(
+            url(&format!("{} focus-url --match-type 'fuzzy' --browser 'arc' -- 'a.com'", launcher)),
+            None,
+            "unknown values stay shell commands"
+        );
+    }
+
+    #[test]
+    fn test_merge_skips_bound_keys() {
+        let mut config = Config::default();
+        config.bindings.push(binding("t", vec![]));
+        config.bindings.push(binding("c", vec![]));
+        let sequence = Binding {
+            sequence: Some("p".to_string()),
+            ..binding("t", vec![])
+        };
+        let conflicts = merge_imported(
+            &mut config,
+            vec![binding("t", vec![]), binding("a", vec![]), sequence],
+        );
+        assert_eq!(conflicts, vec!["t".to_string()]);
+        // Appended after the existing bindings, which keep their order
+        let keys: Vec<(&str, Option<&str>)> = config
+            .bindings
+            .iter()
+            .map(|b| (b.key.as_str(), b.sequence.as_deref()))
+            .collect();
+        assert_eq!(keys, vec![("t", None), ("c", None), ("a", None), ("t", Some("p"))]);
+    }
+}
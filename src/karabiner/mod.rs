@@ -1,4 +1,7 @@
 pub mod backup;
+pub mod diff;
+pub mod import;
+pub mod quote;
 pub mod generator;
 
 pub use generator::*;
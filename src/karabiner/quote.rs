@@ -0,0 +1,179 @@
+//! Quoting for generated shell_commands. Every user-supplied value (app names, URLs,
+//! paths, scripts) reaches /bin/sh or AppleScript through one of these functions,
+//! so no value can end its argument early or run as code.
+
+/// Single-quote `s` for /bin/sh: everything is literal, including newlines;
+/// embedded single quotes become '\''
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Quote a path for the shell. A leading ~ or $HOME stays outside the quotes as
+/// "$HOME", so it expands when Karabiner runs the command (like SCRIPTS_RUNTIME_DIR).
+pub fn shell_path(path: &str) -> String {
+    for home in ["~", "$HOME"] {
+        match path.strip_prefix(home) {
+            Some("") => return "\"$HOME\"".to_string(),
+            Some(rest) if rest.starts_with('/') => {
+                return format!("\"$HOME\"{}", shell_quote(rest))
+            }
+            _ => {}
+        }
+    }
+    shell_quote(path)
+}
+
+/// AppleScript string literal: double-quoted, with backslashes and double quotes escaped
+pub fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Minimal POSIX word splitting: whitespace separated, with '…' and "…" quoting
+/// and backslash escapes. Returns None for unbalanced quotes or shell operators.
+pub fn split_shell_words(input: &str) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => current.push(c),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => current.push(chars.next()?),
+                        c => current.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                current.push(chars.next()?);
+            }
+            ';' | '&' | '|' | '<' | '>' | '`' | '$' => return None,
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    Some(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::process::Command;
+
+    /// Inverse of `applescript_string`, following AppleScript's escapes
+    fn parse_applescript_string(literal: &str) -> Option<String> {
+        let inner = literal.strip_prefix('"')?.strip_suffix('"')?;
+        let mut out = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => out.push(chars.next()?),
+                '"' => return None, // the literal ended early
+                c => out.push(c),
+            }
+        }
+        Some(out)
+    }
+
+    /// The arguments /bin/sh makes of `words`, with HOME=/home/x
+    fn sh_args(words: &str) -> Vec<String> {
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(format!("printf '%s\\0' {}", words))
+            .env("HOME", "/home/x")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let mut args: Vec<String> = String::from_utf8(output.stdout)
+            .unwrap()
+            .split('\0')
+            .map(String::from)
+            .collect();
+        args.pop();
+        args
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("Bob's App"), "'Bob'\\''s App'");
+        assert_eq!(shell_path("~/a b"), "\"$HOME\"'/a b'");
+        assert_eq!(shell_path("~work"), "'~work'");
+        assert_eq!(applescript_string("say \"hi\" \\"), "\"say \\\"hi\\\" \\\\\"");
+    }
+
+    #[test]
+    fn test_split_shell_words() {
+        assert_eq!(
+            split_shell_words("echo $HOME"),
+            None,
+            "unquoted expansion is not a literal word"
+        );
+        assert_eq!(
+            split_shell_words(r#"open -a 'Visual Studio Code'"#).unwrap(),
+            vec!["open", "-a", "Visual Studio Code"]
+        );
+        assert_eq!(
+            split_shell_words(r#"say "a \"b\"" c\ d"#).unwrap(),
+            vec!["say", "a \"b\"", "c d"]
+        );
+        assert_eq!(split_shell_words("echo 'unterminated"), None);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_shell_quote_is_one_word(s in any::<String>()) {
+            prop_assert_eq!(split_shell_words(&shell_quote(&s)), Some(vec![s]));
+        }
+
+        #[test]
+        fn prop_applescript_string_round_trips(s in any::<String>()) {
+            prop_assert_eq!(parse_applescript_string(&applescript_string(&s)), Some(s));
+        }
+    }
+
+    proptest! {
+        // Each case starts a shell, so fewer of them
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_sh_reads_shell_quote_as_one_argument(s in r"[^\x00]*") {
+            let words = format!("{} {}", shell_quote(&s), shell_quote(&s));
+            prop_assert_eq!(sh_args(&words), vec![s.clone(), s]);
+        }
+
+        #[test]
+        fn prop_sh_expands_home_in_shell_path(rest in r"[^\x00]*") {
+            let expected = vec![format!("/home/x/{}", rest)];
+            prop_assert_eq!(sh_args(&shell_path(&format!("~/{}", rest))), expected.clone());
+            prop_assert_eq!(sh_args(&shell_path(&format!("$HOME/{}", rest))), expected);
+            prop_assume!(!rest.starts_with('~') && !rest.starts_with("$HOME"));
+            prop_assert_eq!(sh_args(&shell_path(&rest)), vec![rest]);
+        }
+    }
+}
@@ -1,5 +1,5 @@
-/// Valid Karabiner key codes for autocomplete
-/// Reference: https://karabiner-elements.pqrs.org/docs/json/complex-modifications-manipulator-definition/from/
+//! Valid Karabiner key codes for autocomplete
+//! Reference: https://karabiner-elements.pqrs.org/docs/json/complex-modifications-manipulator-definition/from/
 
 /// Letter keys (a-z)
 pub const LETTER_KEYS: &[&str] = &[
@@ -85,6 +85,58 @@ pub fn is_valid_key(key: &str) -> bool {
     all_key_codes().contains(&key)
 }
 
+/// Printable ASCII that isn't a letter or digit on a US layout: (char, key_code, shift)
+const SYMBOL_KEYS: &[(char, &str, bool)] = &[
+    (' ', "spacebar", false),
+    ('\n', "return_or_enter", false),
+    ('\t', "tab", false),
+    ('-', "hyphen", false),
+    ('_', "hyphen", true),
+    ('=', "equal_sign", false),
+    ('+', "equal_sign", true),
+    ('[', "open_bracket", false),
+    ('{', "open_bracket", true),
+    (']', "close_bracket", false),
+    ('}', "close_bracket", true),
+    ('\\', "backslash", false),
+    ('|', "backslash", true),
+    (';', "semicolon", false),
+    (':', "semicolon", true),
+    ('\'', "quote", false),
+    ('"', "quote", true),
+    ('`', "grave_accent_and_tilde", false),
+    ('~', "grave_accent_and_tilde", true),
+    (',', "comma", false),
+    ('<', "comma", true),
+    ('.', "period", false),
+    ('>', "period", true),
+    ('/', "slash", false),
+    ('?', "slash", true),
+];
+
+/// Shifted number row on a US layout, "!" is shift+1 ... ")" is shift+0
+const SHIFTED_NUMBERS: &str = ")!@#$%^&*(";
+
+/// Key code and whether shift is held to type `c` on a US layout.
+/// None for characters without a key (non-ASCII, control characters).
+pub fn char_to_key(c: char) -> Option<(&'static str, bool)> {
+    let index = |keys: &[&'static str], i: u32| keys.get(i as usize).copied();
+    match c {
+        'a'..='z' => index(LETTER_KEYS, c as u32 - 'a' as u32).map(|k| (k, false)),
+        'A'..='Z' => index(LETTER_KEYS, c as u32 - 'A' as u32).map(|k| (k, true)),
+        '0'..='9' => index(NUMBER_KEYS, c as u32 - '0' as u32).map(|k| (k, false)),
+        _ => {
+            if let Some(i) = SHIFTED_NUMBERS.find(c) {
+                return Some((NUMBER_KEYS[i], true));
+            }
+            SYMBOL_KEYS
+                .iter()
+                .find(|(symbol, _, _)| *symbol == c)
+                .map(|(_, key, shift)| (*key, *shift))
+        }
+    }
+}
+
 /// Get autocomplete suggestions for a partial key input
 pub fn autocomplete(partial: &str) -> Vec<&'static str> {
     if partial.is_empty() {
@@ -109,6 +161,24 @@ mod tests {
         assert!(!is_valid_key("invalid_key"));
     }
 
+    #[test]
+    fn test_char_to_key() {
+        assert_eq!(char_to_key('a'), Some(("a", false)));
+        assert_eq!(char_to_key('Z'), Some(("z", true)));
+        assert_eq!(char_to_key('7'), Some(("7", false)));
+        assert_eq!(char_to_key('@'), Some(("2", true)));
+        assert_eq!(char_to_key(')'), Some(("0", true)));
+        assert_eq!(char_to_key('?'), Some(("slash", true)));
+        assert_eq!(char_to_key(' '), Some(("spacebar", false)));
+        assert_eq!(char_to_key('é'), None);
+
+        // Every printable ASCII character has a key, and every key is valid
+        for c in (' '..='~').chain(['\n', '\t']) {
+            let (key, _) = char_to_key(c).unwrap_or_else(|| panic!("no key for {:?}", c));
+            assert!(is_valid_key(key), "{}", key);
+        }
+    }
+
     #[test]
     fn test_autocomplete() {
         let suggestions = autocomplete("f1");
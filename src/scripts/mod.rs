@@ -1,222 +1,18 @@
 use anyhow::{Context, Result};
 use std::fs;
 use std::os::unix::fs::PermissionsExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::config::persistence::ensure_scripts_dir;
+use crate::karabiner::quote::{shell_path, shell_quote};
 
-/// Embedded url-focus.sh script
+/// Embedded url-focus.sh script. Rules generated by older versions run it;
+/// `rcmdb focus-url` does the work now.
 const URL_FOCUS_SCRIPT: &str = r#"#!/usr/bin/env bash
-# url-focus.sh - Focus or open URL in browser with matching logic
+# url-focus.sh - Kept for rules generated before `rcmdb focus-url`
 # Usage: url-focus.sh <url> <match_type> <browser>
-# match_type: exact | domain | path | glob
 
-URL="$1"
-MATCH_TYPE="$2"
-BROWSER="$3"
-
-# Extract domain from URL
-get_domain() {
-    echo "$1" | sed -E 's|^https?://||' | sed -E 's|/.*||' | sed -E 's|:.*||'
-}
-
-# Extract path from URL (domain + path, no query)
-get_path() {
-    echo "$1" | sed -E 's|^https?://||' | sed -E 's|\?.*||'
-}
-
-# Convert glob pattern to regex
-glob_to_regex() {
-    echo "$1" | sed -E 's/\./\\./g' | sed -E 's/\*/.*/g'
-}
-
-DOMAIN=$(get_domain "$URL")
-PATH_PART=$(get_path "$URL")
-
-case "$BROWSER" in
-    chrome)
-        # Chrome: Full AppleScript tab search
-        osascript <<EOF
-tell application "Google Chrome"
-    set found to false
-    repeat with w in windows
-        set tabIndex to 0
-        repeat with t in tabs of w
-            set tabIndex to tabIndex + 1
-            set tabUrl to URL of t
-            set matched to false
-            
-            if "$MATCH_TYPE" is "exact" then
-                if tabUrl is "$URL" then set matched to true
-            else if "$MATCH_TYPE" is "domain" then
-                if tabUrl contains "$DOMAIN" then set matched to true
-            else if "$MATCH_TYPE" is "path" then
-                if tabUrl contains "$PATH_PART" then set matched to true
-            else if "$MATCH_TYPE" is "glob" then
-                -- Simple glob: check if domain matches
-                if tabUrl contains "$DOMAIN" then set matched to true
-            end if
-            
-            if matched then
-                set active tab index of w to tabIndex
-                set index of w to 1
-                activate
-                set found to true
-                exit repeat
-            end if
-        end repeat
-        if found then exit repeat
-    end repeat
-    
-    if not found then
-        open location "$URL"
-        activate
-    end if
-end tell
-EOF
-        ;;
-    
-    firefox)
-        # Firefox: Limited AppleScript support, use open command
-        # Firefox handles duplicate detection for exact URLs
-        if pgrep -x "firefox" > /dev/null; then
-            # Try to activate Firefox first
-            osascript -e 'tell application "Firefox" to activate'
-            # Open URL (Firefox may focus existing tab for exact match)
-            open -a Firefox "$URL"
-        else
-            open -a Firefox "$URL"
-        fi
-        ;;
-    
-    safari)
-        # Safari: Full AppleScript support
-        osascript <<EOF
-tell application "Safari"
-    set found to false
-    repeat with w in windows
-        set tabIndex to 0
-        repeat with t in tabs of w
-            set tabIndex to tabIndex + 1
-            set tabUrl to URL of t
-            set matched to false
-            
-            if "$MATCH_TYPE" is "exact" then
-                if tabUrl is "$URL" then set matched to true
-            else if "$MATCH_TYPE" is "domain" then
-                if tabUrl contains "$DOMAIN" then set matched to true
-            else if "$MATCH_TYPE" is "path" then
-                if tabUrl contains "$PATH_PART" then set matched to true
-            else if "$MATCH_TYPE" is "glob" then
-                if tabUrl contains "$DOMAIN" then set matched to true
-            end if
-            
-            if matched then
-                set current tab of w to t
-                set index of w to 1
-                activate
-                set found to true
-                exit repeat
-            end if
-        end repeat
-        if found then exit repeat
-    end repeat
-    
-    if not found then
-        open location "$URL"
-        activate
-    end if
-end tell
-EOF
-        ;;
-    
-    arc)
-        # Arc: Similar to Chrome (Chromium-based)
-        osascript <<EOF
-tell application "Arc"
-    set found to false
-    repeat with w in windows
-        set tabIndex to 0
-        repeat with t in tabs of w
-            set tabIndex to tabIndex + 1
-            set tabUrl to URL of t
-            set matched to false
-            
-            if "$MATCH_TYPE" is "exact" then
-                if tabUrl is "$URL" then set matched to true
-            else if "$MATCH_TYPE" is "domain" then
-                if tabUrl contains "$DOMAIN" then set matched to true
-            else if "$MATCH_TYPE" is "path" then
-                if tabUrl contains "$PATH_PART" then set matched to true
-            else if "$MATCH_TYPE" is "glob" then
-                if tabUrl contains "$DOMAIN" then set matched to true
-            end if
-            
-            if matched then
-                set active tab index of w to tabIndex
-                set index of w to 1
-                activate
-                set found to true
-                exit repeat
-            end if
-        end repeat
-        if found then exit repeat
-    end repeat
-    
-    if not found then
-        open location "$URL"
-        activate
-    end if
-end tell
-EOF
-        ;;
-    
-    edge)
-        # Edge: Similar to Chrome (Chromium-based)
-        osascript <<EOF
-tell application "Microsoft Edge"
-    set found to false
-    repeat with w in windows
-        set tabIndex to 0
-        repeat with t in tabs of w
-            set tabIndex to tabIndex + 1
-            set tabUrl to URL of t
-            set matched to false
-            
-            if "$MATCH_TYPE" is "exact" then
-                if tabUrl is "$URL" then set matched to true
-            else if "$MATCH_TYPE" is "domain" then
-                if tabUrl contains "$DOMAIN" then set matched to true
-            else if "$MATCH_TYPE" is "path" then
-                if tabUrl contains "$PATH_PART" then set matched to true
-            else if "$MATCH_TYPE" is "glob" then
-                if tabUrl contains "$DOMAIN" then set matched to true
-            end if
-            
-            if matched then
-                set active tab index of w to tabIndex
-                set index of w to 1
-                activate
-                set found to true
-                exit repeat
-            end if
-        end repeat
-        if found then exit repeat
-    end repeat
-    
-    if not found then
-        open location "$URL"
-        activate
-    end if
-end tell
-EOF
-        ;;
-    
-    *)
-        # Fallback: just open the URL
-        open "$URL"
-        ;;
-esac
+exec "$(dirname "$0")/rcmdb" focus-url --match-type "$2" --browser "$3" -- "$1"
 "#;
 
 /// Embedded center-mouse.sh script
@@ -295,25 +91,46 @@ function run(argv) {
 JSEOF
 "#;
 
+/// The rcmdb binary by a path that survives upgrades. Homebrew runs it from a
+/// versioned Cellar directory but links it into <prefix>/bin.
+fn stable_binary_path(exe: &Path) -> PathBuf {
+    let path = exe.to_string_lossy();
+    match (path.find("/Cellar/"), exe.file_name()) {
+        (Some(i), Some(name)) => Path::new(&path[..i]).join("bin").join(name),
+        _ => exe.to_path_buf(),
+    }
+}
+
+/// scripts/rcmdb: runs `binary`, so generated commands can name rcmdb by a
+/// fixed path. A binary under `home` is referred to through $HOME.
+fn launcher_script(binary: &Path, home: Option<&Path>) -> String {
+    let path = match home.and_then(|home| binary.strip_prefix(home).ok()) {
+        Some(rest) => shell_path(&format!("~/{}", rest.display())),
+        None => shell_quote(&binary.to_string_lossy()),
+    };
+    format!(
+        "#!/usr/bin/env bash\n# rcmdb - Written by `rcmdb apply`: runs the installed rcmdb\n\nexec {} \"$@\"\n",
+        path
+    )
+}
+
+fn write_executable(path: &Path, contents: &str) -> Result<()> {
+    fs::write(path, contents).with_context(|| format!("Failed to write {:?}", path))?;
+    let mut perms = fs::metadata(path)?.permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(path, perms)?;
+    Ok(())
+}
+
 /// Install helper scripts to the config directory
 pub fn install_scripts() -> Result<PathBuf> {
     let scripts_dir = ensure_scripts_dir()?;
 
-    // Install url-focus.sh
-    let url_focus_path = scripts_dir.join("url-focus.sh");
-    fs::write(&url_focus_path, URL_FOCUS_SCRIPT)
-        .with_context(|| format!("Failed to write {:?}", url_focus_path))?;
-    let mut perms = fs::metadata(&url_focus_path)?.permissions();
-    perms.set_mode(0o755);
-    fs::set_permissions(&url_focus_path, perms)?;
-
-    // Install center-mouse.sh
-    let center_mouse_path = scripts_dir.join("center-mouse.sh");
-    fs::write(&center_mouse_path, CENTER_MOUSE_SCRIPT)
-        .with_context(|| format!("Failed to write {:?}", center_mouse_path))?;
-    let mut perms = fs::metadata(&center_mouse_path)?.permissions();
-    perms.set_mode(0o755);
-    fs::set_permissions(&center_mouse_path, perms)?;
+    let exe = std::env::current_exe().context("Failed to locate the rcmdb binary")?;
+    let launcher = launcher_script(&stable_binary_path(&exe), dirs::home_dir().as_deref());
+    write_executable(&scripts_dir.join("rcmdb"), &launcher)?;
+    write_executable(&scripts_dir.join("url-focus.sh"), URL_FOCUS_SCRIPT)?;
+    write_executable(&scripts_dir.join("center-mouse.sh"), CENTER_MOUSE_SCRIPT)?;
 
     Ok(scripts_dir)
 }
@@ -343,6 +160,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_scripts_pass_values_to_osascript_as_arguments() {
+        // An unquoted heredoc expands $URL into the AppleScript source, where a
+        // crafted URL could close the string literal and run its own code
+        for script in [URL_FOCUS_SCRIPT, CENTER_MOUSE_SCRIPT] {
+            assert!(!script.contains("<<EOF"), "heredocs must be quoted: <<'EOF'");
+            assert!(!script.contains("<< EOF"), "heredocs must be quoted: <<'EOF'");
+        }
+    }
+
+    #[test]
+    fn test_launcher_survives_upgrades() {
+        assert_eq!(
+            stable_binary_path(Path::new(
+                "/opt/homebrew/Cellar/karabiner-rcmd-binder/0.5.2/bin/rcmdb"
+            )),
+            Path::new("/opt/homebrew/bin/rcmdb")
+        );
+        assert_eq!(
+            stable_binary_path(Path::new("/usr/local/bin/rcmdb")),
+            Path::new("/usr/local/bin/rcmdb")
+        );
+
+        let home = Path::new("/Users/me");
+        let launcher = launcher_script(Path::new("/Users/me/.cargo/bin/rcmdb"), Some(home));
+        assert!(launcher.starts_with("#!/usr/bin/env bash"));
+        assert!(launcher.contains("exec \"$HOME\"'/.cargo/bin/rcmdb' \"$@\""));
+        assert!(!launcher.contains("/Users/"), "must not embed absolute user path");
+        let launcher = launcher_script(Path::new("/opt/homebrew/bin/rcmdb"), Some(home));
+        assert!(launcher.contains("exec '/opt/homebrew/bin/rcmdb' \"$@\""));
+    }
+
     #[test]
     fn test_scripts_no_baked_absolute_paths() {
         // Scripts must not embed save-time user paths — use $HOME or relative refs only
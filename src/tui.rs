@@ -0,0 +1,787 @@
+use anyhow::Result;
+use crossterm::{
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{backend::CrosstermBackend, Terminal};
+use std::io;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use crate::app::{ActionEditorField, App, EditorField, InputMode, SettingsField, Tab};
+use crate::app_discovery::{self, DiscoveredApp};
+use crate::browser_profiles::profile_names;
+use crate::commands::apply::apply_config;
+use crate::config::{load_config, ActionMode, KeyModifier, UrlMatchType};
+use crate::ui;
+
+/// Run the interactive TUI until the user quits
+pub fn run() -> Result<()> {
+    // Setup terminal
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    // Load config and create app
+    let config = load_config()?;
+    let mut app = App::new(config);
+
+    // Create channel for app discovery
+    let (tx, rx) = mpsc::channel();
+
+    // Load cached apps immediately and start background discovery
+    app.discovered_apps = app.config.cached_apps.clone();
+    app.generate_dynamic_bindings();
+    app.load_karabiner_profiles();
+    app.start_app_discovery();
+    spawn_app_discovery(tx.clone());
+
+    // Run the main loop
+    let res = run_app(&mut terminal, &mut app, rx, tx);
+
+    // Restore terminal
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
+
+    if let Err(err) = res {
+        eprintln!("Error: {:?}", err);
+    }
+
+    Ok(())
+}
+
+fn run_app<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    rx: Receiver<Vec<DiscoveredApp>>,
+    tx: Sender<Vec<DiscoveredApp>>,
+) -> Result<()>
+where
+    <B as ratatui::backend::Backend>::Error: Send + Sync + 'static,
+{
+    loop {
+        terminal.draw(|f| ui::draw(f, app))?;
+
+        // Check for app discovery results (non-blocking)
+        if let Ok(apps) = rx.try_recv() {
+            app.finish_app_discovery(apps);
+        }
+
+        if event::poll(std::time::Duration::from_millis(50))? {
+            if let Event::Key(key) = event::read()? {
+                // Clear status on any keypress
+                app.clear_status();
+
+                match &app.input_mode {
+                    InputMode::Normal => handle_normal_mode(app, key.code, key.modifiers, &tx)?,
+                    InputMode::Editing => handle_editing_mode(app, key.code, key.modifiers)?,
+                }
+            }
+        }
+
+        if app.should_quit {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_normal_mode(
+    app: &mut App,
+    key: KeyCode,
+    _modifiers: KeyModifiers,
+    tx: &Sender<Vec<DiscoveredApp>>,
+) -> Result<()> {
+    match key {
+        KeyCode::Char('q') => {
+            app.should_quit = true;
+        }
+        KeyCode::Tab => {
+            app.next_tab();
+        }
+        KeyCode::BackTab => {
+            app.prev_tab();
+        }
+        KeyCode::Char('s') => {
+            // Save to karabiner
+            save_to_karabiner(app)?;
+        }
+        _ => match app.tab {
+            Tab::Bindings => handle_bindings_normal(app, key, tx)?,
+            Tab::Settings => handle_settings_normal(app, key)?,
+        },
+    }
+    Ok(())
+}
+
+fn handle_bindings_normal(
+    app: &mut App,
+    key: KeyCode,
+    _tx: &Sender<Vec<DiscoveredApp>>,
+) -> Result<()> {
+    match key {
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.next_binding();
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.prev_binding();
+        }
+        KeyCode::Char('a') => {
+            if app.is_on_dynamic_binding() {
+                app.add_dynamic_binding();
+            } else {
+                app.start_new_binding();
+            }
+        }
+        KeyCode::Char('e') | KeyCode::Enter => {
+            app.start_edit_binding();
+        }
+        KeyCode::Char('d') => {
+            app.delete_binding();
+        }
+        KeyCode::Char('i') => {
+            app.import_from_karabiner();
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn handle_settings_normal(app: &mut App, key: KeyCode) -> Result<()> {
+    match key {
+        KeyCode::Char('j') | KeyCode::Down => app.next_settings_field(),
+        KeyCode::Char('k') | KeyCode::Up => app.next_settings_field(),
+        KeyCode::Char(' ') if app.settings_field == SettingsField::AnchorKey => {
+            app.toggle_caps_lock_hyper();
+        }
+        KeyCode::Char(' ') if app.settings_field == SettingsField::CenterMouse => {
+            app.toggle_center_mouse();
+        }
+        KeyCode::Char(' ') if app.settings_field == SettingsField::Profiles => {
+            app.toggle_profile();
+        }
+        KeyCode::Left | KeyCode::Char('h') | KeyCode::Char(',') | KeyCode::Char('<') => {
+            match app.settings_field {
+                SettingsField::AnchorKey => app.prev_anchor_key(),
+                SettingsField::DefaultBrowser => app.prev_browser(),
+                SettingsField::CenterMouse => app.toggle_center_mouse(),
+                SettingsField::Profiles => app.prev_profile(),
+            }
+        }
+        KeyCode::Right | KeyCode::Char('l') | KeyCode::Char('.') | KeyCode::Char('>') => {
+            match app.settings_field {
+                SettingsField::AnchorKey => app.next_anchor_key(),
+                SettingsField::DefaultBrowser => app.next_browser(),
+                SettingsField::CenterMouse => app.toggle_center_mouse(),
+                SettingsField::Profiles => app.next_profile(),
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn handle_editing_mode(app: &mut App, key: KeyCode, _modifiers: KeyModifiers) -> Result<()> {
+    let editor = match app.binding_editor.as_mut() {
+        Some(e) => e,
+        None => return Ok(()),
+    };
+
+    // Check if we're editing an action
+    if let Some(ref mut action_editor) = editor.action_editor {
+        // Esc: exit edit mode or cancel action editor
+        if key == KeyCode::Esc {
+            if action_editor.edit_mode {
+                action_editor.edit_mode = false;
+                app.show_autocomplete = false;
+            } else {
+                app.show_autocomplete = false;
+                editor.cancel_action_edit();
+            }
+            return Ok(());
+        }
+
+        // Tab/Shift-Tab: always navigate fields, exit edit mode
+        if key == KeyCode::Tab {
+            app.show_autocomplete = false;
+            action_editor.edit_mode = false;
+            action_editor.next_field();
+            return Ok(());
+        }
+        if key == KeyCode::BackTab {
+            app.show_autocomplete = false;
+            action_editor.edit_mode = false;
+            action_editor.prev_field();
+            return Ok(());
+        }
+
+        // Save shortcut: only in Nav mode
+        if !action_editor.edit_mode {
+            if let KeyCode::Char('s') = key {
+                app.show_autocomplete = false;
+                editor.finish_action_edit();
+                return Ok(());
+            }
+        }
+
+        // Field-specific handling
+        match action_editor.field {
+            ActionEditorField::Target => {
+                if action_editor.edit_mode {
+                    // EDIT MODE: handle text input
+                    match key {
+                        KeyCode::Enter => {
+                            // Select autocomplete or finish editing
+                            if app.show_autocomplete {
+                                if let Some(suggestion) =
+                                    app.autocomplete_suggestions.get(app.autocomplete_selected)
+                                {
+                                    action_editor.target = suggestion.value.clone();
+                                    if action_editor.action_type == crate::app::ActionType::App {
+                                        action_editor.bundle_id = Some(suggestion.bundle_id.clone());
+                                    }
+                                }
+                                app.show_autocomplete = false;
+                            } else if action_editor.has_script_body() {
+                                // Script bodies are multi-line: Esc/Tab finish instead
+                                action_editor.target.push('\n');
+                            } else {
+                                // Finish editing
+                                action_editor.edit_mode = false;
+                                app.show_autocomplete = false;
+                            }
+                        }
+                        KeyCode::Char(c)
+                            if action_editor.action_type == crate::app::ActionType::Delay
+                                && !c.is_ascii_digit() => {}
+                        KeyCode::Char(c) => {
+                            action_editor.target.push(c);
+                            let (action_type, target) =
+                                (action_editor.action_type, action_editor.target.clone());
+                            app.update_target_autocomplete(action_type, &target);
+                        }
+                        KeyCode::Backspace => {
+                            action_editor.target.pop();
+                            let (action_type, target) =
+                                (action_editor.action_type, action_editor.target.clone());
+                            app.update_target_autocomplete(action_type, &target);
+                        }
+                        KeyCode::Down if app.show_autocomplete => {
+                            app.next_autocomplete();
+                        }
+                        KeyCode::Up if app.show_autocomplete => {
+                            app.prev_autocomplete();
+                        }
+                        _ => {}
+                    }
+                } else {
+                    // NAV MODE: Enter starts editing
+                    if key == KeyCode::Enter {
+                        action_editor.edit_mode = true;
+                        let (action_type, target) =
+                            (action_editor.action_type, action_editor.target.clone());
+                        app.update_target_autocomplete(action_type, &target);
+                    }
+                }
+            }
+            ActionEditorField::Input | ActionEditorField::WithApp => {
+                // Plain text fields; WithApp autocompletes apps and stores the bundle ID
+                let with_app = action_editor.field == ActionEditorField::WithApp;
+                let text = if with_app {
+                    &mut action_editor.with_app
+                } else {
+                    &mut action_editor.input
+                };
+                if action_editor.edit_mode {
+                    match key {
+                        KeyCode::Enter => {
+                            if app.show_autocomplete {
+                                if let Some(suggestion) =
+                                    app.autocomplete_suggestions.get(app.autocomplete_selected)
+                                {
+                                    *text = suggestion.bundle_id.clone();
+                                }
+                            }
+                            action_editor.edit_mode = false;
+                            app.show_autocomplete = false;
+                        }
+                        KeyCode::Down if app.show_autocomplete => app.next_autocomplete(),
+                        KeyCode::Up if app.show_autocomplete => app.prev_autocomplete(),
+                        KeyCode::Char(c) => {
+                            text.push(c);
+                            if with_app {
+                                let partial = text.clone();
+                                app.update_app_autocomplete(&partial);
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            text.pop();
+                            if with_app {
+                                let partial = text.clone();
+                                app.update_app_autocomplete(&partial);
+                            }
+                        }
+                        _ => {}
+                    }
+                } else if key == KeyCode::Enter {
+                    action_editor.edit_mode = true;
+                    if with_app {
+                        let partial = text.clone();
+                        app.update_app_autocomplete(&partial);
+                    }
+                }
+            }
+            ActionEditorField::ScriptSource | ActionEditorField::Toggle => {
+                if matches!(
+                    key,
+                    KeyCode::Left
+                        | KeyCode::Right
+                        | KeyCode::Char('<' | '>' | ',' | '.' | ' ')
+                ) {
+                    let flag = if action_editor.field == ActionEditorField::Toggle {
+                        &mut action_editor.toggle
+                    } else {
+                        &mut action_editor.script_from_file
+                    };
+                    *flag = !*flag;
+                }
+            }
+            ActionEditorField::MatchOptions => {
+                // Multiselect like Modifiers
+                let count = action_editor.url_options.flags().len();
+                match key {
+                    KeyCode::Left | KeyCode::Char('<') | KeyCode::Char(',') => {
+                        action_editor.url_option_cursor =
+                            (action_editor.url_option_cursor + count - 1) % count;
+                    }
+                    KeyCode::Right | KeyCode::Char('>') | KeyCode::Char('.') => {
+                        action_editor.url_option_cursor =
+                            (action_editor.url_option_cursor + 1) % count;
+                    }
+                    KeyCode::Char(' ') | KeyCode::Enter => {
+                        action_editor.url_options.toggle(action_editor.url_option_cursor);
+                    }
+                    _ => {}
+                }
+            }
+            ActionEditorField::Profile => {
+                // Selector over the browser's Local State profiles, then none
+                let names = profile_names(&app.config, action_editor.browser.as_ref());
+                let choices: Vec<Option<String>> =
+                    names.into_iter().map(Some).chain([None]).collect();
+                let idx = choices
+                    .iter()
+                    .position(|p| *p == action_editor.profile)
+                    .unwrap_or(choices.len() - 1);
+                match key {
+                    KeyCode::Left | KeyCode::Char('<') | KeyCode::Char(',') => {
+                        let prev_idx = idx.checked_sub(1).unwrap_or(choices.len() - 1);
+                        action_editor.profile = choices[prev_idx].clone();
+                    }
+                    KeyCode::Right | KeyCode::Char('>') | KeyCode::Char('.') => {
+                        action_editor.profile = choices[(idx + 1) % choices.len()].clone();
+                    }
+                    _ => {}
+                }
+            }
+            ActionEditorField::Modifiers => {
+                // Multiselect: move with arrows, space toggles
+                let count = KeyModifier::all().len();
+                match key {
+                    KeyCode::Left | KeyCode::Char('<') | KeyCode::Char(',') => {
+                        action_editor.modifier_cursor =
+                            (action_editor.modifier_cursor + count - 1) % count;
+                    }
+                    KeyCode::Right | KeyCode::Char('>') | KeyCode::Char('.') => {
+                        action_editor.modifier_cursor = (action_editor.modifier_cursor + 1) % count;
+                    }
+                    KeyCode::Char(' ') | KeyCode::Enter => action_editor.toggle_modifier(),
+                    _ => {}
+                }
+            }
+            ActionEditorField::Type | ActionEditorField::MatchType | ActionEditorField::Browser => {
+                // Selector fields: always responsive to arrow keys (no edit mode needed)
+                match key {
+                    KeyCode::Left | KeyCode::Char('<') | KeyCode::Char(',') => {
+                        match action_editor.field {
+                            ActionEditorField::Type => {
+                                action_editor.action_type = action_editor.action_type.prev();
+                            }
+                            ActionEditorField::MatchType => {
+                                let types = UrlMatchType::all();
+                                let idx = types
+                                    .iter()
+                                    .position(|t| t == &action_editor.match_type)
+                                    .unwrap_or(0);
+                                let prev_idx = idx.checked_sub(1).unwrap_or(types.len() - 1);
+                                action_editor.match_type = types[prev_idx].clone();
+                            }
+                            ActionEditorField::Browser => {
+                                let browsers = app.config.browser_choices();
+                                match &action_editor.browser {
+                                    None => {
+                                        action_editor.browser = Some(browsers.last().unwrap().clone());
+                                    }
+                                    Some(b) => {
+                                        let idx = browsers.iter().position(|x| x == b).unwrap_or(0);
+                                        if idx == 0 {
+                                            action_editor.browser = None;
+                                        } else {
+                                            action_editor.browser = Some(browsers[idx - 1].clone());
+                                        }
+                                    }
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    KeyCode::Right | KeyCode::Char('>') | KeyCode::Char('.') => {
+                        match action_editor.field {
+                            ActionEditorField::Type => {
+                                action_editor.action_type = action_editor.action_type.next();
+                            }
+                            ActionEditorField::MatchType => {
+                                let types = UrlMatchType::all();
+                                let idx = types
+                                    .iter()
+                                    .position(|t| t == &action_editor.match_type)
+                                    .unwrap_or(0);
+                                let next_idx = (idx + 1) % types.len();
+                                action_editor.match_type = types[next_idx].clone();
+                            }
+                            ActionEditorField::Browser => {
+                                let browsers = app.config.browser_choices();
+                                match &action_editor.browser {
+                                    None => {
+                                        action_editor.browser = Some(browsers[0].clone());
+                                    }
+                                    Some(b) => {
+                                        let idx = browsers.iter().position(|x| x == b).unwrap_or(0);
+                                        if idx == browsers.len() - 1 {
+                                            action_editor.browser = None;
+                                        } else {
+                                            action_editor.browser = Some(browsers[idx + 1].clone());
+                                        }
+                                    }
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    // Binding editor (not action editor)
+
+    // Esc: exit edit mode or cancel editor
+    if key == KeyCode::Esc {
+        if editor.edit_mode {
+            editor.edit_mode = false;
+            app.show_autocomplete = false;
+        } else {
+            app.show_autocomplete = false;
+            app.cancel_edit();
+        }
+        return Ok(());
+    }
+
+    // Tab/Shift-Tab: always navigate fields (regardless of mode)
+    if key == KeyCode::Tab {
+        app.show_autocomplete = false;
+        editor.edit_mode = false; // Exit edit mode when switching fields
+        editor.next_field();
+        return Ok(());
+    }
+    if key == KeyCode::BackTab {
+        app.show_autocomplete = false;
+        editor.edit_mode = false; // Exit edit mode when switching fields
+        editor.prev_field();
+        return Ok(());
+    }
+
+    // Field-specific handling based on edit mode
+    match editor.field {
+        EditorField::Key
+        | EditorField::Sequence
+        | EditorField::Description
+        | EditorField::CycleReset => {
+            let is_key_field = matches!(editor.field, EditorField::Key | EditorField::Sequence);
+            if editor.edit_mode {
+                // EDIT MODE: handle text input
+                match key {
+                    KeyCode::Enter => {
+                        // Finish editing if not using autocomplete
+                        if app.show_autocomplete && is_key_field {
+                            // Select autocomplete
+                            if let Some(suggestion) = app
+                                .autocomplete_suggestions
+                                .get(app.autocomplete_selected)
+                                .cloned()
+                            {
+                                if let Some(field) = editor.key_field_mut() {
+                                    *field = suggestion.value;
+                                }
+                            }
+                            app.show_autocomplete = false;
+                        } else {
+                            // Finish editing
+                            editor.edit_mode = false;
+                            app.show_autocomplete = false;
+                        }
+                    }
+                    _ => {
+                        if is_key_field {
+                            handle_key_field_input(app, key)?;
+                        } else if editor.field == EditorField::CycleReset {
+                            handle_cycle_reset_field_input(app, key)?;
+                        } else {
+                            handle_description_field_input(app, key)?;
+                        }
+                    }
+                }
+            } else {
+                // NAV MODE: shortcuts work
+                match key {
+                    KeyCode::Enter => {
+                        // Start editing
+                        editor.edit_mode = true;
+                        if let Some(field) = editor.key_field_mut() {
+                            let key_clone = field.clone();
+                            app.update_autocomplete(&key_clone);
+                        }
+                    }
+                    KeyCode::Char('s') if !editor.key.is_empty() => {
+                        // Save binding (works in all NAV mode fields)
+                        app.save_binding();
+                    }
+                    _ => {}
+                }
+            }
+        }
+        EditorField::OnlyIn | EditorField::ExceptIn => {
+            if editor.edit_mode {
+                // EDIT MODE: type to filter discovered apps, Enter adds the entry
+                match key {
+                    KeyCode::Enter => {
+                        let entry = if app.show_autocomplete {
+                            app.autocomplete_suggestions
+                                .get(app.autocomplete_selected)
+                                .map(|s| s.bundle_id.clone())
+                                .unwrap_or_else(|| editor.app_input.clone())
+                        } else {
+                            editor.app_input.clone()
+                        };
+                        if entry.is_empty() {
+                            editor.edit_mode = false;
+                        } else {
+                            editor.add_app_condition(entry);
+                            editor.app_input.clear();
+                        }
+                        app.show_autocomplete = false;
+                    }
+                    KeyCode::Char(c) => {
+                        editor.app_input.push(c);
+                        let input = editor.app_input.clone();
+                        app.update_app_autocomplete(&input);
+                    }
+                    KeyCode::Backspace => {
+                        if editor.app_input.pop().is_none() {
+                            // Empty input: remove the last entry
+                            if let Some(list) = editor.app_list_mut() {
+                                list.pop();
+                            }
+                        }
+                        let input = editor.app_input.clone();
+                        app.update_app_autocomplete(&input);
+                    }
+                    KeyCode::Down if app.show_autocomplete => {
+                        app.next_autocomplete();
+                    }
+                    KeyCode::Up if app.show_autocomplete => {
+                        app.prev_autocomplete();
+                    }
+                    _ => {}
+                }
+            } else {
+                match key {
+                    KeyCode::Enter => {
+                        editor.edit_mode = true;
+                        let input = editor.app_input.clone();
+                        app.update_app_autocomplete(&input);
+                    }
+                    KeyCode::Char('d') => {
+                        if let Some(list) = editor.app_list_mut() {
+                            list.pop();
+                        }
+                    }
+                    KeyCode::Char('s') if !editor.key.is_empty() => {
+                        app.save_binding();
+                    }
+                    _ => {}
+                }
+            }
+        }
+        EditorField::Actions => {
+            // Actions field: always in "list nav mode", edit_mode doesn't apply
+            match key {
+                KeyCode::Char('s') if !editor.key.is_empty() => {
+                    // Save binding
+                    app.save_binding();
+                }
+                KeyCode::Enter | KeyCode::Char('e') if !editor.actions.is_empty() => {
+                    // Edit selected action
+                    editor.start_editing_action();
+                }
+                KeyCode::Char('a') => {
+                    editor.start_adding_action();
+                }
+                KeyCode::Char('f') => {
+                    editor.focus_aware = !editor.focus_aware;
+                }
+                KeyCode::Char('m') => {
+                    editor.mode = match editor.mode {
+                        ActionMode::Cycle => ActionMode::All,
+                        ActionMode::All => ActionMode::Cycle,
+                    };
+                }
+                KeyCode::Char('d') => {
+                    editor.delete_selected_action();
+                }
+                KeyCode::Char('k') | KeyCode::Up if editor.selected_action > 0 => {
+                    editor.selected_action -= 1;
+                }
+                KeyCode::Char('j') | KeyCode::Down
+                    if editor.selected_action < editor.actions.len().saturating_sub(1) =>
+                {
+                    editor.selected_action += 1;
+                }
+                KeyCode::Char('K') => {
+                    editor.move_action_up();
+                }
+                KeyCode::Char('J') => {
+                    editor.move_action_down();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_key_field_input(app: &mut App, key: KeyCode) -> Result<()> {
+    match key {
+        KeyCode::Char(c) => {
+            if let Some(field) = app.binding_editor.as_mut().and_then(|e| e.key_field_mut()) {
+                // Allow typing to filter autocomplete
+                field.push(c);
+                let key_clone = field.clone();
+                app.update_autocomplete(&key_clone);
+            }
+        }
+        KeyCode::Backspace => {
+            if let Some(field) = app.binding_editor.as_mut().and_then(|e| e.key_field_mut()) {
+                field.pop();
+                let key_clone = field.clone();
+                app.update_autocomplete(&key_clone);
+            }
+        }
+        KeyCode::Down if app.show_autocomplete => {
+            app.next_autocomplete();
+        }
+        KeyCode::Up if app.show_autocomplete => {
+            app.prev_autocomplete();
+        }
+        KeyCode::Right => {
+            // Accept autocomplete
+            if let Some(suggestion) = app.select_autocomplete() {
+                if let Some(field) = app.binding_editor.as_mut().and_then(|e| e.key_field_mut()) {
+                    *field = suggestion.value;
+                }
+                app.show_autocomplete = false;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_description_field_input(app: &mut App, key: KeyCode) -> Result<()> {
+    let editor = app.binding_editor.as_mut().unwrap();
+
+    match key {
+        KeyCode::Char(c) => {
+            editor.description.push(c);
+        }
+        KeyCode::Backspace => {
+            editor.description.pop();
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_cycle_reset_field_input(app: &mut App, key: KeyCode) -> Result<()> {
+    let editor = app.binding_editor.as_mut().unwrap();
+
+    match key {
+        KeyCode::Char(c) if c.is_ascii_digit() => {
+            editor.cycle_reset.push(c);
+        }
+        KeyCode::Backspace => {
+            editor.cycle_reset.pop();
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn spawn_app_discovery(tx: Sender<Vec<DiscoveredApp>>) {
+    thread::spawn(move || {
+        let apps = app_discovery::discover_all_apps();
+        let _ = tx.send(apps);
+    });
+}
+
+/// Failures are shown in the status line rather than returned, so the TUI stays open
+/// with the unsaved edits still in memory
+fn save_to_karabiner(app: &mut App) -> Result<()> {
+    // Validate, save config, install scripts and apply to karabiner
+    let report = match apply_config(&mut app.config) {
+        Ok(report) => report,
+        Err(e) => {
+            app.set_status(format!("Save failed: {:#}", e));
+            return Ok(());
+        }
+    };
+
+    let mut msg = match report.resolved.len() {
+        0 => "Saved to karabiner.json (backup created)".to_string(),
+        resolved => format!("Saved ({} bundle IDs updated)", resolved),
+    };
+    // The first warning in full, so a missing path or app can be found
+    if let Some(warning) = report.warnings.first() {
+        msg.push_str(&format!(". {}", warning));
+        if report.warnings.len() > 1 {
+            msg.push_str(&format!(" (+{} more warnings)", report.warnings.len() - 1));
+        }
+    }
+    app.set_status(msg);
+    Ok(())
+}
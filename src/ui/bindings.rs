@@ -7,6 +7,7 @@ use ratatui::{
 };
 
 use crate::app::App;
+use crate::config::Binding;
 
 pub fn draw_bindings(frame: &mut Frame, app: &App, area: Rect) {
     let saved_count = app.config.bindings.len();
@@ -18,7 +19,8 @@ pub fn draw_bindings(frame: &mut Frame, app: &App, area: Rect) {
         .iter()
         .enumerate()
         .map(|(i, binding)| {
-            let key = format!("{:<12}", binding.display_key(&app.config.settings.anchor_key));
+            let previous = i.checked_sub(1).and_then(|p| app.config.bindings.get(p));
+            let key = format!("{:<12}", display_key(binding, previous, app));
             let actions = format!("{:<40}", truncate(&binding.actions_summary(), 38));
             let desc = truncate(&binding.description, 30);
 
@@ -31,11 +33,33 @@ pub fn draw_bindings(frame: &mut Frame, app: &App, area: Rect) {
                 Style::default()
             };
 
-            let line = Line::from(vec![
+            let mut spans = vec![
                 Span::styled(key, style),
                 Span::styled(actions, style.fg(Color::Cyan)),
                 Span::styled(desc, style.fg(Color::DarkGray)),
-            ]);
+            ];
+            for extra in [binding.hold_summary(), binding.double_tap_summary()]
+                .into_iter()
+                .flatten()
+            {
+                spans.push(Span::styled(
+                    format!(" [{}]", truncate(&extra, 30)),
+                    style.fg(Color::Cyan),
+                ));
+            }
+            if let Some(modified) = binding.modified_summary() {
+                spans.push(Span::styled(
+                    format!(" [{}]", truncate(&modified, 30)),
+                    style.fg(Color::Cyan),
+                ));
+            }
+            if let Some(conditions) = binding.conditions_summary() {
+                spans.push(Span::styled(
+                    format!(" [{}]", truncate(&conditions, 30)),
+                    style.fg(Color::Magenta),
+                ));
+            }
+            let line = Line::from(spans);
 
             ListItem::new(line)
         })
@@ -97,6 +121,23 @@ pub fn draw_bindings(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_stateful_widget(list, area, &mut state);
 }
 
+/// Key column; sequence bindings are nested under their leader:
+/// "rcmd+g › p" for the first of a group, then "       └ i" aligned below it
+fn display_key(binding: &Binding, previous: Option<&Binding>, app: &App) -> String {
+    let anchor_key = &app.config.settings.anchor_key;
+    let Some(second) = &binding.sequence else {
+        return binding.display_key(anchor_key);
+    };
+
+    let leader = format!("{}+{}", anchor_key.display_prefix(), binding.key);
+    let continues_group = previous.is_some_and(|p| p.key == binding.key && p.is_sequence());
+    if continues_group {
+        format!("{:>width$} {}", "└", second, width = leader.chars().count() + 2)
+    } else {
+        format!("{} › {}", leader, second)
+    }
+}
+
 fn truncate(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
         s.to_string()
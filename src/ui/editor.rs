@@ -7,6 +7,7 @@ use ratatui::{
 };
 
 use crate::app::{ActionEditorField, ActionType, App, EditorField};
+use crate::config::{ActionMode, KeyModifier};
 
 pub fn draw_editor(frame: &mut Frame, app: &App, area: Rect) {
     let editor = match &app.binding_editor {
@@ -49,7 +50,11 @@ pub fn draw_editor(frame: &mut Frame, app: &App, area: Rect) {
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3), // Key
+            Constraint::Length(3), // Sequence
             Constraint::Length(3), // Description
+            Constraint::Length(3), // Only in
+            Constraint::Length(3), // Except in
+            Constraint::Length(3), // Cycle reset
             Constraint::Min(5),    // Actions list
         ])
         .split(inner);
@@ -70,6 +75,14 @@ pub fn draw_editor(frame: &mut Frame, app: &App, area: Rect) {
     let key_text = Paragraph::new(editor.key.as_str()).block(key_block);
     frame.render_widget(key_text, chunks[0]);
 
+    // Sequence field (second stroke after anchor+key)
+    let seq_block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Then Key (leader sequence, empty = none) ")
+        .border_style(field_style(editor.field == EditorField::Sequence));
+    let seq_text = Paragraph::new(editor.sequence.as_str()).block(seq_block);
+    frame.render_widget(seq_text, chunks[1]);
+
     // Description field
     let desc_style = if editor.field == EditorField::Description {
         Style::default()
@@ -83,7 +96,44 @@ pub fn draw_editor(frame: &mut Frame, app: &App, area: Rect) {
         .title(" Description ")
         .border_style(desc_style);
     let desc_text = Paragraph::new(editor.description.as_str()).block(desc_block);
-    frame.render_widget(desc_text, chunks[1]);
+    frame.render_widget(desc_text, chunks[2]);
+
+    // App condition fields
+    draw_app_list(
+        frame,
+        editor,
+        EditorField::OnlyIn,
+        " Only In (apps) ",
+        &editor.only_in,
+        chunks[3],
+    );
+    draw_app_list(
+        frame,
+        editor,
+        EditorField::ExceptIn,
+        " Except In (apps) ",
+        &editor.except_in,
+        chunks[4],
+    );
+
+    // Cycle reset (only matters for cycling bindings)
+    let default_reset = match app.config.settings.cycle_reset_ms {
+        0 => "never".to_string(),
+        ms => format!("{} ms", ms),
+    };
+    let reset_block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Cycle Reset (ms, 0 = never) ")
+        .border_style(field_style(editor.field == EditorField::CycleReset));
+    let reset_text = if editor.cycle_reset.is_empty() && !editor.edit_mode {
+        Line::from(Span::styled(
+            format!("(default: {})", default_reset),
+            Style::default().fg(Color::DarkGray),
+        ))
+    } else {
+        Line::from(editor.cycle_reset.as_str())
+    };
+    frame.render_widget(Paragraph::new(reset_text).block(reset_block), chunks[5]);
 
     // Actions list
     let actions_style = if editor.field == EditorField::Actions {
@@ -115,30 +165,54 @@ pub fn draw_editor(frame: &mut Frame, app: &App, area: Rect) {
         })
         .collect();
 
+    // Hold, double-tap and modified actions are edited in config.toml; the editor keeps them as they are
+    let cycle_label = match editor.mode {
+        ActionMode::All => "all at once",
+        ActionMode::Cycle if editor.focus_aware => "cycle order, focus-aware",
+        ActionMode::Cycle => "cycle order",
+    };
+    let extras: Vec<String> = [
+        editor.base.hold_summary(),
+        editor.base.double_tap_summary(),
+        editor.base.modified_summary(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let actions_title = if extras.is_empty() {
+        format!(" Actions ({}) ", cycle_label)
+    } else {
+        format!(" Actions ({}) | {} ", cycle_label, extras.join(" | "))
+    };
     let actions_block = Block::default()
         .borders(Borders::ALL)
-        .title(" Actions (cycle order) ")
+        .title(actions_title)
         .border_style(actions_style);
 
     if action_items.is_empty() {
         let empty = Paragraph::new("  No actions. Press 'a' to add.")
             .style(Style::default().fg(Color::DarkGray))
             .block(actions_block);
-        frame.render_widget(empty, chunks[2]);
+        frame.render_widget(empty, chunks[6]);
     } else {
         let actions_list = List::new(action_items).block(actions_block);
         let mut state = ListState::default();
         state.select(Some(editor.selected_action));
-        frame.render_stateful_widget(actions_list, chunks[2], &mut state);
+        frame.render_stateful_widget(actions_list, chunks[6], &mut state);
     }
 
     // Render autocomplete LAST so it appears on top of all other widgets
-    if app.show_autocomplete && editor.field == EditorField::Key {
+    let key_field = match editor.field {
+        EditorField::Key => Some(chunks[0]),
+        EditorField::Sequence => Some(chunks[1]),
+        _ => None,
+    };
+    if let Some(field_area) = key_field.filter(|_| app.show_autocomplete) {
         let suggestions_height = (app.autocomplete_suggestions.len() as u16 + 2).min(8);
         let autocomplete_area = Rect {
-            x: chunks[0].x + 1,
-            y: chunks[0].y + chunks[0].height,
-            width: chunks[0].width.saturating_sub(2).min(35),
+            x: field_area.x + 1,
+            y: field_area.y + field_area.height,
+            width: field_area.width.saturating_sub(2).min(35),
             height: suggestions_height,
         };
         // Only draw if within screen bounds
@@ -146,6 +220,54 @@ pub fn draw_editor(frame: &mut Frame, app: &App, area: Rect) {
             draw_autocomplete(frame, app, autocomplete_area);
         }
     }
+
+    let app_field = match editor.field {
+        EditorField::OnlyIn => Some(chunks[3]),
+        EditorField::ExceptIn => Some(chunks[4]),
+        _ => None,
+    };
+    if let Some(field_area) = app_field.filter(|_| app.show_autocomplete) {
+        let suggestions_height = (app.autocomplete_suggestions.len() as u16 + 2).min(8);
+        let autocomplete_area = Rect {
+            x: field_area.x + 1,
+            y: field_area.y + field_area.height,
+            width: field_area.width.saturating_sub(2).min(45),
+            height: suggestions_height,
+        };
+        if autocomplete_area.y + autocomplete_area.height <= area.height {
+            draw_autocomplete(frame, app, autocomplete_area);
+        }
+    }
+}
+
+/// Comma-separated app list, followed by the pending input while editing
+fn draw_app_list(
+    frame: &mut Frame,
+    editor: &crate::app::BindingEditor,
+    field: EditorField,
+    title: &str,
+    entries: &[String],
+    area: Rect,
+) {
+    let selected = editor.field == field;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(field_style(selected));
+
+    let mut spans = vec![Span::raw(entries.join(", "))];
+    if selected && editor.edit_mode {
+        if !entries.is_empty() {
+            spans.push(Span::raw(", "));
+        }
+        spans.push(Span::styled(
+            editor.app_input.clone(),
+            Style::default().fg(Color::Green),
+        ));
+    } else if entries.is_empty() {
+        spans.push(Span::styled("(any app)", Style::default().fg(Color::DarkGray)));
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)).block(block), area);
 }
 
 fn draw_action_editor(
@@ -170,11 +292,21 @@ fn draw_action_editor(
 
     // Different layout based on action type
     let num_fields = match action_editor.action_type {
-        ActionType::Url => 4,
+        ActionType::Url => 6,
+        ActionType::App
+        | ActionType::Keystroke
+        | ActionType::Shortcut
+        | ActionType::AppleScript
+        | ActionType::Open => 3,
         _ => 2,
     };
+    // Inline scripts get a multi-line body
+    let multiline_target = action_editor.has_script_body();
 
     let mut constraints = vec![Constraint::Length(3); num_fields];
+    if multiline_target {
+        constraints[1] = Constraint::Length(10);
+    }
     constraints.push(Constraint::Min(0));
 
     let chunks = Layout::default()
@@ -198,6 +330,13 @@ fn draw_action_editor(
         ActionType::App => " App Name ",
         ActionType::Url => " URL ",
         ActionType::Shell => " Shell Command ",
+        ActionType::Open => " File or Folder (~/ expands) ",
+        ActionType::Shortcut => " Shortcut Name ",
+        ActionType::AppleScript if multiline_target => " Script (Enter: new line, Esc: done) ",
+        ActionType::AppleScript => " Script File ",
+        ActionType::Delay => " Delay (ms, before the next action) ",
+        ActionType::Text => " Text to Type ",
+        ActionType::Keystroke => " Key Code ",
     };
     let target_block = Block::default()
         .borders(Borders::ALL)
@@ -206,6 +345,53 @@ fn draw_action_editor(
     let target_text = Paragraph::new(action_editor.target.as_str()).block(target_block);
     frame.render_widget(target_text, chunks[1]);
 
+    if action_editor.action_type == ActionType::Shortcut {
+        let input_block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Input Text (optional) ")
+            .border_style(field_style(action_editor.field == ActionEditorField::Input));
+        let input_text = Paragraph::new(action_editor.input.as_str()).block(input_block);
+        frame.render_widget(input_text, chunks[2]);
+    }
+
+    if action_editor.action_type == ActionType::App {
+        let toggle_block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Press Again to Hide (space) ")
+            .border_style(field_style(action_editor.field == ActionEditorField::Toggle));
+        let (label, color) = if action_editor.toggle {
+            ("ON", Color::Green)
+        } else {
+            ("OFF", Color::DarkGray)
+        };
+        let toggle_text = Paragraph::new(Span::styled(label, Style::default().fg(color)));
+        frame.render_widget(toggle_text.block(toggle_block), chunks[2]);
+    }
+
+    if action_editor.action_type == ActionType::Open {
+        let app_block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Open With (app bundle ID, empty = default app) ")
+            .border_style(field_style(action_editor.field == ActionEditorField::WithApp));
+        let app_name = app
+            .discovered_apps
+            .iter()
+            .find(|a| !action_editor.with_app.is_empty() && a.bundle_id == action_editor.with_app)
+            .map(|a| format!(" ({})", a.name))
+            .unwrap_or_default();
+        let app_text = Paragraph::new(format!("{}{}", action_editor.with_app, app_name));
+        frame.render_widget(app_text.block(app_block), chunks[2]);
+    }
+
+    if action_editor.action_type == ActionType::AppleScript {
+        let source_block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Script From (</> to change) ")
+            .border_style(field_style(action_editor.field == ActionEditorField::ScriptSource));
+        let source = if action_editor.script_from_file { "File" } else { "Inline source" };
+        frame.render_widget(Paragraph::new(source).block(source_block), chunks[2]);
+    }
+
     // URL-specific fields
     if action_editor.action_type == ActionType::Url {
         // Match type
@@ -217,6 +403,29 @@ fn draw_action_editor(
         let match_text = Paragraph::new(action_editor.match_type.as_str()).block(match_block);
         frame.render_widget(match_text, chunks[2]);
 
+        // Ignored URL parts (exact and glob only)
+        let selected = action_editor.field == ActionEditorField::MatchOptions;
+        let options_block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Ignore for exact/glob (</> move, space toggle) ")
+            .border_style(field_style(selected));
+        let spans: Vec<Span> = action_editor
+            .url_options
+            .flags()
+            .iter()
+            .enumerate()
+            .map(|(i, (label, on))| {
+                let checked = if *on { "[x]" } else { "[ ]" };
+                let style = if selected && i == action_editor.url_option_cursor {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default()
+                };
+                Span::styled(format!(" {} {} ", checked, label), style)
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(Line::from(spans)).block(options_block), chunks[3]);
+
         // Browser override
         let browser_style = field_style(action_editor.field == ActionEditorField::Browser);
         let browser_block = Block::default()
@@ -229,16 +438,53 @@ fn draw_action_editor(
             .map(|b| b.display_name())
             .unwrap_or("(use default)");
         let browser_para = Paragraph::new(browser_text).block(browser_block);
-        frame.render_widget(browser_para, chunks[3]);
+        frame.render_widget(browser_para, chunks[4]);
+
+        // Chromium profile, offered from the browser's Local State
+        let profile_block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Profile (</> to change, empty=any window) ")
+            .border_style(field_style(action_editor.field == ActionEditorField::Profile));
+        let profile_text = action_editor.profile.as_deref().unwrap_or("(none)");
+        frame.render_widget(Paragraph::new(profile_text).block(profile_block), chunks[5]);
+    }
+
+    // Keystroke modifiers
+    if action_editor.action_type == ActionType::Keystroke {
+        let selected = action_editor.field == ActionEditorField::Modifiers;
+        let modifiers_block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Modifiers (</> move, space toggle) ")
+            .border_style(field_style(selected));
+        let spans: Vec<Span> = KeyModifier::all()
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let checked = if action_editor.modifiers.contains(m) { "[x]" } else { "[ ]" };
+                let style = if selected && i == action_editor.modifier_cursor {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default()
+                };
+                Span::styled(format!(" {} {} ", checked, m.short_name()), style)
+            })
+            .collect();
+        let modifiers_para = Paragraph::new(Line::from(spans)).block(modifiers_block);
+        frame.render_widget(modifiers_para, chunks[2]);
     }
 
     // Render autocomplete LAST so it appears on top of all other widgets
-    if app.show_autocomplete && action_editor.field == ActionEditorField::Target {
+    let anchor = match action_editor.field {
+        ActionEditorField::Target => Some(chunks[1]),
+        ActionEditorField::WithApp => Some(chunks[2]),
+        _ => None,
+    };
+    if let Some(anchor) = anchor.filter(|_| app.show_autocomplete) {
         let suggestions_height = (app.autocomplete_suggestions.len() as u16 + 2).min(8);
         let autocomplete_area = Rect {
-            x: chunks[1].x + 1,
-            y: chunks[1].y + chunks[1].height,
-            width: chunks[1].width.saturating_sub(2).min(45),
+            x: anchor.x + 1,
+            y: anchor.y + anchor.height,
+            width: anchor.width.saturating_sub(2).min(45),
             height: suggestions_height,
         };
         draw_autocomplete(frame, app, autocomplete_area);
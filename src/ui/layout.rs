@@ -77,7 +77,7 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     } else {
         let help = match (&app.input_mode, &app.binding_editor) {
             (InputMode::Normal, None) => match app.tab {
-                Tab::Bindings => "(a)dd (e)dit (d)elete (j/k)nav (Tab)switch (s)ave (q)uit",
+                Tab::Bindings => "(a)dd (e)dit (d)elete (i)mport (j/k)nav (Tab)switch (s)ave (q)uit",
                 Tab::Settings => "(←→)change (Tab)switch (s)ave (q)uit",
             },
             (InputMode::Editing, Some(editor)) => {
@@ -85,17 +85,40 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
                     // Action Editor
                     if action_editor.edit_mode {
                         // EDIT mode: typing in Target field
-                        if action_editor.action_type == crate::app::ActionType::App {
+                        if action_editor.action_type == crate::app::ActionType::App
+                            || action_editor.field == crate::app::ActionEditorField::WithApp
+                        {
                             "[EDIT] Type app name | (↑↓)autocomplete (Enter)finish (Tab)next (Esc)exit"
+                        } else if action_editor.action_type == crate::app::ActionType::Open
+                            && action_editor.field == crate::app::ActionEditorField::Target
+                        {
+                            "[EDIT] Type path (start with ~/ or /) | (↑↓)autocomplete (Enter)pick/finish (Esc)exit"
+                        } else if action_editor.field == crate::app::ActionEditorField::Target
+                            && action_editor.has_script_body()
+                        {
+                            "[EDIT] Type script | (Enter)new line (Esc)finish (Tab)finish+next"
+                        } else if action_editor.action_type == crate::app::ActionType::Keystroke {
+                            "[EDIT] Type key code | (↑↓)autocomplete (Enter)finish (Tab)next (Esc)exit"
                         } else {
                             "[EDIT] Type target | (Enter)finish (Tab)next (Esc)exit"
                         }
                     } else {
                         // NAV mode
                         match action_editor.field {
-                            crate::app::ActionEditorField::Target => {
+                            crate::app::ActionEditorField::Target
+                                if action_editor.has_script_body() =>
+                            {
+                                "[NAV] (Enter)edit script, where Enter adds a line and Esc finishes (s)ave (Tab)next (Esc)cancel"
+                            }
+                            crate::app::ActionEditorField::Target
+                            | crate::app::ActionEditorField::Input
+                            | crate::app::ActionEditorField::WithApp => {
                                 "[NAV] (Enter)edit (s)ave (Tab)next (Esc)cancel"
                             }
+                            crate::app::ActionEditorField::Modifiers
+                            | crate::app::ActionEditorField::MatchOptions => {
+                                "[NAV] (←→)move (Space)toggle (s)ave (Tab)next (Esc)cancel"
+                            }
                             _ => {
                                 "[NAV] (←→)change (s)ave (Tab)next (Esc)cancel"
                             }
@@ -109,19 +132,34 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
                             crate::app::EditorField::Key => {
                                 "[EDIT] Type key | (↑↓)autocomplete (Enter)finish (Tab)next (Esc)exit"
                             }
+                            crate::app::EditorField::Sequence => {
+                                "[EDIT] Type second key (empty = none) | (↑↓)autocomplete (Enter)finish (Tab)next (Esc)exit"
+                            }
+                            crate::app::EditorField::CycleReset => {
+                                "[EDIT] Type milliseconds (empty = default, 0 = never) | (Enter)finish (Tab)next (Esc)exit"
+                            }
                             crate::app::EditorField::Description => {
                                 "[EDIT] Type description | (Enter)finish (Tab)next (Esc)exit"
                             }
+                            crate::app::EditorField::OnlyIn | crate::app::EditorField::ExceptIn => {
+                                "[EDIT] Type app or /regex/ | (↑↓)autocomplete (Enter)add (Bksp)remove (Esc)exit"
+                            }
                             _ => "[NAV]",
                         }
                     } else {
                         // NAV mode
                         match editor.field {
-                            crate::app::EditorField::Key | crate::app::EditorField::Description => {
+                            crate::app::EditorField::Key
+                            | crate::app::EditorField::Sequence
+                            | crate::app::EditorField::Description
+                            | crate::app::EditorField::CycleReset => {
                                 "[NAV] (Enter)edit (s)ave (Tab)next (Esc)cancel"
                             }
+                            crate::app::EditorField::OnlyIn | crate::app::EditorField::ExceptIn => {
+                                "[NAV] (Enter)add app (d)elete last (s)ave (Tab)next (Esc)cancel"
+                            }
                             crate::app::EditorField::Actions => {
-                                "[NAV] (a)dd (e)dit (d)elete (j/k)nav (J/K)move (s)ave (Tab)next (Esc)cancel"
+                                "[NAV] (a)dd (e)dit (d)elete (j/k)nav (J/K)move (f)ocus-aware (m)ode (s)ave (Tab)next (Esc)cancel"
                             }
                         }
                     }
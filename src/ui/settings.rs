@@ -15,12 +15,16 @@ pub fn draw_settings(frame: &mut Frame, app: &App, area: Rect) {
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
+    let profile_choices = app.profile_choices();
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3), // Anchor key
+            Constraint::Length(3), // Anchor tap action
             Constraint::Length(3), // Default browser
             Constraint::Length(3), // Center mouse
+            Constraint::Length(profile_choices.len().max(1) as u16 + 2), // Profiles
             Constraint::Min(0),    // Padding
         ])
         .split(inner);
@@ -34,15 +38,49 @@ pub fn draw_settings(frame: &mut Frame, app: &App, area: Rect) {
         Style::default()
     };
 
+    let settings = &app.config.settings;
+    let anchor_title = if settings.anchor_key.is_hyper() {
+        " Anchor Key (</> to change, space: caps_lock → hyper rule) "
+    } else {
+        " Anchor Key (</> to change) "
+    };
     let anchor_block = Block::default()
         .borders(Borders::ALL)
-        .title(" Anchor Key (</> to change) ")
+        .title(anchor_title)
         .border_style(anchor_style);
 
-    let anchor_text = app.config.settings.anchor_key.display_name();
-    let anchor_para = Paragraph::new(anchor_text).block(anchor_block);
+    let mut anchor_spans = vec![Span::raw(settings.anchor_key.display_name())];
+    if settings.anchor_key.is_hyper() {
+        let (label, color) = if settings.caps_lock_hyper {
+            ("  caps_lock rule: ON", Color::Green)
+        } else {
+            ("  caps_lock rule: OFF", Color::DarkGray)
+        };
+        anchor_spans.push(Span::styled(label, Style::default().fg(color)));
+    }
+    let anchor_para = Paragraph::new(Line::from(anchor_spans)).block(anchor_block);
     frame.render_widget(anchor_para, chunks[0]);
 
+    // Anchor tap action: read-only, set in config.toml
+    let tap_block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Anchor Tap (anchor_tap_action in config.toml) ");
+    let tap_line = match (&settings.anchor_tap_action, settings.anchor_tap_key()) {
+        (None, _) => Line::from(Span::styled(" (none)", Style::default().fg(Color::DarkGray))),
+        (Some(action), Some(key)) => Line::from(vec![
+            Span::raw(format!(" {} alone → ", key)),
+            Span::styled(action.display_summary(), Style::default().fg(Color::Cyan)),
+        ]),
+        (Some(action), None) => Line::from(vec![
+            Span::styled(action.display_summary(), Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                "  (needs a single-key anchor or the caps_lock rule)",
+                Style::default().fg(Color::Red),
+            ),
+        ]),
+    };
+    frame.render_widget(Paragraph::new(tap_line).block(tap_block), chunks[1]);
+
     // Default browser
     let browser_style = if app.settings_field == SettingsField::DefaultBrowser {
         Style::default()
@@ -59,7 +97,7 @@ pub fn draw_settings(frame: &mut Frame, app: &App, area: Rect) {
 
     let browser_text = app.config.settings.default_browser.display_name();
     let browser_para = Paragraph::new(browser_text).block(browser_block);
-    frame.render_widget(browser_para, chunks[1]);
+    frame.render_widget(browser_para, chunks[2]);
 
     // Center mouse toggle
     let cm_style = if app.settings_field == SettingsField::CenterMouse {
@@ -84,5 +122,49 @@ pub fn draw_settings(frame: &mut Frame, app: &App, area: Rect) {
         Span::styled(cm_value, cm_value_style),
     ]))
     .block(cm_block);
-    frame.render_widget(cm_para, chunks[2]);
+    frame.render_widget(cm_para, chunks[3]);
+
+    // Target profiles
+    let profiles_selected = app.settings_field == SettingsField::Profiles;
+    let profiles_style = if profiles_selected {
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    };
+    let configured = &app.config.settings.profiles;
+    let profiles_title = if configured.is_empty() {
+        " Karabiner Profiles (</> move, space toggle; none = first profile) "
+    } else {
+        " Karabiner Profiles (</> move, space toggle) "
+    };
+    let profile_lines: Vec<Line> = if profile_choices.is_empty() {
+        vec![Line::from(Span::styled(
+            " (no profiles found in karabiner.json)",
+            Style::default().fg(Color::DarkGray),
+        ))]
+    } else {
+        profile_choices
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let checked = if configured.contains(name) { "[x]" } else { "[ ]" };
+                let missing = if app.karabiner_profiles.contains(name) { "" } else { " (missing)" };
+                let style = if profiles_selected && i == app.profile_cursor {
+                    Style::default().fg(Color::Yellow)
+                } else if missing.is_empty() {
+                    Style::default()
+                } else {
+                    Style::default().fg(Color::Red)
+                };
+                Line::from(Span::styled(format!(" {} {}{}", checked, name, missing), style))
+            })
+            .collect()
+    };
+    let profiles_block = Block::default()
+        .borders(Borders::ALL)
+        .title(profiles_title)
+        .border_style(profiles_style);
+    frame.render_widget(Paragraph::new(profile_lines).block(profiles_block), chunks[4]);
 }
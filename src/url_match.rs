@@ -0,0 +1,192 @@
+//! What counts as "the" tab for a URL action. A target, match type and options compile
+//! to one regex that `rcmdb focus-url` tests tab URLs with.
+
+use regex::Regex;
+
+use crate::config::{UrlMatchOptions, UrlMatchType};
+
+/// Any URL scheme, for targets written without one ("github.com/org")
+const ANY_SCHEME: &str = "[a-z][a-z0-9+.-]*://";
+
+/// What may follow a domain or path: nothing, or the rest of the URL after / ? or #
+const ANY_REST: &str = "(?:[/?#].*)?";
+
+/// The compiled `url_pattern`. Fails only for an invalid Regex target.
+pub fn url_regex(
+    target: &str,
+    match_type: &UrlMatchType,
+    options: &UrlMatchOptions,
+) -> Result<Regex, regex::Error> {
+    Regex::new(&url_pattern(target, match_type, options))
+}
+
+/// The regex source for a target.
+///
+/// - exact: the whole URL; a target without a scheme matches any scheme
+/// - domain: same host, any port, path or query
+/// - path: the target's host and path, or anything beneath it
+/// - glob: like exact, with `*` matching any run of characters
+/// - regex: the target itself, searched anywhere in the URL
+///
+/// The options loosen exact and glob matches; domain and path already ignore
+/// the query, fragment and trailing slash.
+fn url_pattern(target: &str, match_type: &UrlMatchType, options: &UrlMatchOptions) -> String {
+    let (scheme, rest) = match target.split_once("://") {
+        Some((scheme, rest)) => (format!("{}://", regex::escape(scheme)), rest),
+        None => (ANY_SCHEME.to_string(), target),
+    };
+    match match_type {
+        UrlMatchType::Regex => target.to_string(),
+        UrlMatchType::Domain => {
+            let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+            let host = host.split(':').next().unwrap_or_default();
+            format!("^{}{}(?::[0-9]+)?{}$", ANY_SCHEME, regex::escape(host), ANY_REST)
+        }
+        UrlMatchType::Path => {
+            let path = rest.split(['?', '#']).next().unwrap_or_default();
+            format!("^{}{}{}$", ANY_SCHEME, regex::escape(path.trim_end_matches('/')), ANY_REST)
+        }
+        UrlMatchType::Exact => format!("^{}{}$", scheme, whole_url(rest, options, regex::escape)),
+        UrlMatchType::Glob => format!("^{}{}$", scheme, whole_url(rest, options, glob)),
+    }
+}
+
+/// Pattern for the URL after its scheme, split at the query and fragment so the
+/// options can make each optional
+fn whole_url(rest: &str, options: &UrlMatchOptions, literal: fn(&str) -> String) -> String {
+    let (rest, fragment) = match rest.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (rest, None),
+    };
+    let (path, query) = match rest.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (rest, None),
+    };
+
+    let mut pattern = if options.ignore_trailing_slash {
+        format!("{}/?", literal(path.trim_end_matches('/')))
+    } else {
+        literal(path)
+    };
+    match query {
+        _ if options.ignore_query => pattern.push_str(r"(?:\?[^#]*)?"),
+        Some(query) => pattern.push_str(&format!(r"\?{}", literal(query))),
+        None => {}
+    }
+    match fragment {
+        _ if options.ignore_fragment => pattern.push_str("(?:#.*)?"),
+        Some(fragment) => pattern.push_str(&format!("#{}", literal(fragment))),
+        None => {}
+    }
+    pattern
+}
+
+/// `*` matches any run of characters, everything else is literal
+fn glob(s: &str) -> String {
+    s.split('*').map(regex::escape).collect::<Vec<_>>().join(".*")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(target: &str, match_type: UrlMatchType, options: UrlMatchOptions) -> Vec<bool> {
+        let regex = url_regex(target, &match_type, &options).unwrap();
+        [
+            "https://github.com/org/repo",
+            "https://github.com/org/repo/",
+            "https://github.com/org/repo?tab=issues",
+            "https://github.com/org/repo#readme",
+            "https://github.com/org/repo/pulls",
+            "https://github.com/organization",
+            "http://github.com:8080/",
+            "https://gist.github.com/org",
+        ]
+        .iter()
+        .map(|url| regex.is_match(url))
+        .collect()
+    }
+
+    const NONE: UrlMatchOptions = UrlMatchOptions {
+        ignore_query: false,
+        ignore_fragment: false,
+        ignore_trailing_slash: false,
+    };
+
+    #[test]
+    fn test_exact() {
+        let exact = |target| matches(target, UrlMatchType::Exact, NONE);
+        let expected = [true, false, false, false, false, false, false, false];
+        assert_eq!(exact("https://github.com/org/repo"), expected);
+        assert_eq!(exact("github.com/org/repo"), expected, "any scheme");
+        assert_eq!(
+            exact("http://github.com/org/repo"),
+            [false; 8],
+            "an explicit scheme must match"
+        );
+        assert_eq!(
+            exact("https://github.com/org/repo?tab=issues"),
+            [false, false, true, false, false, false, false, false]
+        );
+
+        let loose = UrlMatchOptions {
+            ignore_query: true,
+            ignore_fragment: true,
+            ignore_trailing_slash: true,
+        };
+        assert_eq!(
+            matches("https://github.com/org/repo/", UrlMatchType::Exact, loose),
+            [true, true, true, true, false, false, false, false]
+        );
+        let no_slash = UrlMatchOptions {
+            ignore_trailing_slash: true,
+            ..NONE
+        };
+        assert_eq!(
+            matches("https://github.com/org/repo", UrlMatchType::Exact, no_slash),
+            [true, true, false, false, false, false, false, false]
+        );
+    }
+
+    #[test]
+    fn test_domain_and_path() {
+        assert_eq!(
+            matches("https://github.com/anything", UrlMatchType::Domain, NONE),
+            [true, true, true, true, true, true, true, false]
+        );
+        assert_eq!(
+            matches("github.com/org/repo/", UrlMatchType::Path, NONE),
+            [true, true, true, true, true, false, false, false]
+        );
+    }
+
+    #[test]
+    fn test_glob() {
+        assert_eq!(
+            matches("https://github.com/org/*", UrlMatchType::Glob, NONE),
+            [true, true, true, true, true, false, false, false]
+        );
+        assert_eq!(
+            matches("*github.com/org*", UrlMatchType::Glob, NONE),
+            [true, true, true, true, true, true, false, true]
+        );
+        // Regex metacharacters in a glob are literal
+        assert_eq!(
+            matches("https://github.com/org/re.o", UrlMatchType::Glob, NONE),
+            [false; 8]
+        );
+        assert_eq!(
+            url_pattern("a.com/(x)*", &UrlMatchType::Glob, &NONE),
+            format!("^{}a\\.com/\\(x\\).*$", ANY_SCHEME)
+        );
+    }
+
+    #[test]
+    fn test_regex() {
+        assert_eq!(
+            matches(r"github\.com/org/repo/?$", UrlMatchType::Regex, NONE),
+            [true, true, false, false, false, false, false, false]
+        );
+        assert!(url_regex("(", &UrlMatchType::Regex, &NONE).is_err());
+    }
+}
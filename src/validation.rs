@@ -1,47 +1,218 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use std::collections::HashSet;
 use std::process::Command;
 
-use crate::config::{Action, Config};
+use crate::config::{expand_home, Action, ActionMode, Binding, Browser, BrowserFamily, Config};
+use crate::karabiner::quote::applescript_string;
+use crate::keycodes::is_valid_key;
+use crate::url_match::url_regex;
 
-/// Validate and update config by resolving bundle IDs for App actions
-pub fn validate_and_update_config(config: &mut Config) -> Result<Vec<String>> {
-    let mut warnings = Vec::new();
-
-    for binding in &mut config.bindings {
-        for action in &mut binding.actions {
-            if let Action::App { target, bundle_id } = action {
-                // Skip if bundle ID already exists and is not empty
-                if bundle_id.as_ref().map_or(false, |id| !id.is_empty()) {
-                    continue;
+/// Check that every binding's keys are valid and that no leader key is also bound directly
+pub fn check_bindings(bindings: &[Binding]) -> Result<()> {
+    for binding in bindings {
+        if !is_valid_key(&binding.key) {
+            bail!("Invalid key: '{}'", binding.key);
+        }
+        if let Some(second) = binding.sequence.as_deref().filter(|s| !is_valid_key(s)) {
+            bail!("Invalid sequence key: '{}' (after {})", second, binding.key);
+        }
+        if binding.is_sequence() && !binding.double_tap_actions.is_empty() {
+            bail!("Double-tap actions don't work on leader sequences (on {})", binding.key);
+        }
+        // Hold and double-tap actions always run all at once, so they may contain delays
+        let cycled = binding
+            .actions
+            .iter()
+            .chain(binding.modified.iter().flat_map(|m| &m.actions))
+            .map(|action| (action, binding.mode == ActionMode::Cycle));
+        let held = binding
+            .hold_actions
+            .iter()
+            .chain(&binding.double_tap_actions)
+            .map(|action| (action, false));
+        for (action, cycle) in cycled.chain(held) {
+            match action {
+                Action::Keystroke { key_code, .. } if !is_valid_key(key_code) => {
+                    bail!("Invalid keystroke key: '{}' (on {})", key_code, binding.key);
                 }
-
-                // Try to resolve bundle ID from app name
-                match try_resolve_bundle_id(target) {
-                    Ok(resolved_id) => {
-                        *bundle_id = Some(resolved_id.clone());
-                        warnings.push(format!(
-                            "Updated '{}' with bundle ID: {}",
-                            target, resolved_id
-                        ));
-                    }
-                    Err(_) => {
-                        warnings.push(format!(
-                            "Warning: Could not resolve bundle ID for '{}'. App may not launch correctly.", 
-                            target
-                        ));
+                Action::Delay { .. } if cycle => {
+                    bail!("Delay actions need mode = \"all\" (on {})", binding.key);
+                }
+                Action::Url {
+                    target,
+                    match_type,
+                    options,
+                    ..
+                } => {
+                    if let Err(e) = url_regex(target, match_type, options) {
+                        bail!("Invalid URL regex '{}' (on {}): {}", target, binding.key, e);
                     }
                 }
+                _ => {}
             }
         }
     }
 
-    Ok(warnings)
+    let leaders: HashSet<&str> = bindings
+        .iter()
+        .filter(|b| b.is_sequence())
+        .map(|b| b.key.as_str())
+        .collect();
+    if let Some(b) = bindings
+        .iter()
+        .find(|b| !b.is_sequence() && leaders.contains(b.key.as_str()))
+    {
+        bail!("'{}' is a leader key and can't also be bound on its own", b.key);
+    }
+    Ok(())
+}
+
+/// Check the `[[browsers]]` definitions, and that every browser named is built in or defined
+pub fn check_browsers(config: &Config) -> Result<()> {
+    let mut names = HashSet::new();
+    for definition in &config.browsers {
+        if definition.name.trim().is_empty() {
+            bail!("A [[browsers]] entry has no name");
+        }
+        if Browser::from(definition.name.clone()).builtin_definition().is_some() {
+            bail!("'{}' is a built-in browser and can't be redefined", definition.name);
+        }
+        if !names.insert(definition.name.as_str()) {
+            bail!("Browser '{}' is defined twice", definition.name);
+        }
+        if definition.application().is_none() {
+            bail!("Browser '{}' needs an app or bundle_id", definition.name);
+        }
+    }
+
+    let url_browsers = config
+        .bindings
+        .iter()
+        .flat_map(|b| {
+            b.actions
+                .iter()
+                .chain(b.modified.iter().flat_map(|m| &m.actions))
+                .chain(&b.hold_actions)
+                .chain(&b.double_tap_actions)
+        })
+        .chain(&config.settings.anchor_tap_action)
+        .filter_map(|action| match action {
+            Action::Url {
+                browser, profile, ..
+            } => Some((
+                browser.as_ref().unwrap_or(&config.settings.default_browser),
+                profile,
+            )),
+            _ => None,
+        });
+    for (browser, profile) in url_browsers.chain([(&config.settings.default_browser, &None)]) {
+        let Some(definition) = config.browser_definition(browser) else {
+            bail!("Unknown browser '{}': add a [[browsers]] entry for it", browser.as_str());
+        };
+        if let Some(profile) = profile {
+            if definition.family != BrowserFamily::Chromium {
+                bail!(
+                    "Profile '{}' needs a chromium-family browser, not '{}'",
+                    profile,
+                    browser.as_str()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// What validation filled in or found wrong, for the caller to report
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    /// One message per App action whose bundle ID was resolved
+    pub resolved: Vec<String>,
+    /// Problems that don't stop a save: unresolvable apps, missing Open paths
+    pub warnings: Vec<String>,
+}
+
+/// Validate and update config by resolving bundle IDs for App actions
+pub fn validate_and_update_config(config: &mut Config) -> Result<ValidationReport> {
+    check_bindings(&config.bindings)?;
+    check_browsers(config)?;
+    let app_actions = config
+        .bindings
+        .iter_mut()
+        .flat_map(|binding| {
+            binding
+                .actions
+                .iter_mut()
+                .chain(binding.modified.iter_mut().flat_map(|m| &mut m.actions))
+                .chain(&mut binding.hold_actions)
+                .chain(&mut binding.double_tap_actions)
+        })
+        .chain(&mut config.settings.anchor_tap_action);
+    let mut report = ValidationReport::default();
+    resolve_bundle_ids(app_actions, try_resolve_bundle_id, &mut report);
+
+    for binding in &config.bindings {
+        let open_paths = binding
+            .actions
+            .iter()
+            .chain(binding.modified.iter().flat_map(|m| &m.actions))
+            .chain(&binding.hold_actions)
+            .chain(&binding.double_tap_actions)
+            .filter_map(|action| match action {
+                Action::Open { path, .. } => Some(path),
+                _ => None,
+            });
+        for path in open_paths {
+            if !expand_home(path).exists() {
+                report.warnings.push(format!(
+                    "Warning: '{}' does not exist (binding {})",
+                    path, binding.key
+                ));
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Fill in missing bundle IDs of App actions with `resolve`, reporting each outcome
+fn resolve_bundle_ids<'a>(
+    actions: impl Iterator<Item = &'a mut Action>,
+    resolve: impl Fn(&str) -> Result<String>,
+    report: &mut ValidationReport,
+) {
+    for action in actions {
+        if let Action::App {
+            target, bundle_id, ..
+        } = action
+        {
+            // Skip if bundle ID already exists and is not empty
+            if bundle_id.as_ref().is_some_and(|id| !id.is_empty()) {
+                continue;
+            }
+
+            // Try to resolve bundle ID from app name
+            match resolve(target) {
+                Ok(resolved_id) => {
+                    *bundle_id = Some(resolved_id.clone());
+                    report
+                        .resolved
+                        .push(format!("Updated '{}' with bundle ID: {}", target, resolved_id));
+                }
+                Err(_) => {
+                    report.warnings.push(format!(
+                        "Warning: Could not resolve bundle ID for '{}'. App may not launch correctly.",
+                        target
+                    ));
+                }
+            }
+        }
+    }
 }
 
 /// Try to resolve bundle ID from app name using osascript
 fn try_resolve_bundle_id(app_name: &str) -> Result<String> {
     let output = Command::new("osascript")
-        .args(&["-e", &format!("id of application \"{}\"", app_name)])
+        .args(["-e", &format!("id of application {}", applescript_string(app_name))])
         .output()?;
 
     if !output.status.success() {
@@ -59,6 +230,181 @@ fn try_resolve_bundle_id(app_name: &str) -> Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{BrowserDefinition, ExtraModifier, ModifiedActions, UrlMatchType};
+
+    fn binding(key: &str, sequence: Option<&str>) -> Binding {
+        Binding {
+            key: key.to_string(),
+            sequence: sequence.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_check_bindings() {
+        assert!(check_bindings(&[binding("g", Some("p")), binding("g", Some("o"))]).is_ok());
+        assert!(check_bindings(&[binding("g", Some("p")), binding("t", None)]).is_ok());
+        assert!(check_bindings(&[binding("g", Some("p")), binding("g", None)]).is_err());
+        assert!(check_bindings(&[binding("g", Some("pp"))]).is_err());
+        assert!(check_bindings(&[binding("gg", None)]).is_err());
+
+        let mut delayed = binding("w", None);
+        delayed.actions = vec![Action::Delay { ms: 100 }];
+        assert!(check_bindings(std::slice::from_ref(&delayed)).is_err());
+        delayed.mode = ActionMode::All;
+        assert!(check_bindings(&[delayed]).is_ok());
+
+        let mut held = binding("n", None);
+        held.hold_actions = vec![Action::Delay { ms: 100 }];
+        assert!(check_bindings(std::slice::from_ref(&held)).is_ok());
+        held.hold_actions.push(Action::Keystroke {
+            key_code: "nope".to_string(),
+            modifiers: Vec::new(),
+        });
+        assert!(check_bindings(&[held]).is_err());
+
+        let mut url = binding("u", None);
+        url.actions = vec![Action::Url {
+            target: "github\\.com/(org".to_string(),
+            match_type: UrlMatchType::Regex,
+            browser: None,
+            profile: None,
+            options: Default::default(),
+        }];
+        assert!(check_bindings(std::slice::from_ref(&url)).is_err());
+        url.actions[0] = Action::Url {
+            target: "github.com/(org".to_string(),
+            match_type: UrlMatchType::Glob,
+            browser: None,
+            profile: None,
+            options: Default::default(),
+        };
+        assert!(check_bindings(&[url]).is_ok());
+
+        let mut double_tap = binding("g", Some("p"));
+        double_tap.double_tap_actions = vec![Action::Delay { ms: 100 }];
+        assert!(check_bindings(std::slice::from_ref(&double_tap)).is_err());
+        double_tap.sequence = None;
+        assert!(check_bindings(&[double_tap]).is_ok());
+    }
+
+    #[test]
+    fn test_check_browsers() {
+        let brave = BrowserDefinition {
+            name: "brave".to_string(),
+            app: Some("Brave Browser".to_string()),
+            bundle_id: None,
+            family: BrowserFamily::Chromium,
+            user_data_dir: None,
+        };
+        let mut url = binding("b", None);
+        url.hold_actions = vec![Action::Url {
+            target: "github.com".to_string(),
+            match_type: UrlMatchType::Domain,
+            browser: Some(Browser::Custom("brave".to_string())),
+            profile: None,
+            options: Default::default(),
+        }];
+        let mut config = Config {
+            bindings: vec![url],
+            ..Default::default()
+        };
+        assert!(check_browsers(&config).is_err(), "brave isn't defined");
+
+        config.browsers = vec![brave.clone()];
+        assert!(check_browsers(&config).is_ok());
+        config.settings.default_browser = Browser::Custom("zen".to_string());
+        assert!(check_browsers(&config).is_err(), "zen isn't defined");
+        config.settings.default_browser = Browser::Custom("brave".to_string());
+        assert!(check_browsers(&config).is_ok());
+
+        let in_profile = |browser: Option<Browser>| Action::Url {
+            target: "mail.google.com".to_string(),
+            match_type: UrlMatchType::Domain,
+            browser,
+            profile: Some("Work".to_string()),
+            options: Default::default(),
+        };
+        config.bindings[0].actions = vec![in_profile(Some(Browser::Custom("brave".to_string())))];
+        assert!(check_browsers(&config).is_ok());
+        config.bindings[0].actions = vec![in_profile(Some(Browser::Safari))];
+        assert!(check_browsers(&config).is_err(), "Safari has no profiles");
+        config.bindings[0].actions = vec![in_profile(None)];
+        assert!(check_browsers(&config).is_ok(), "the default is brave");
+
+        config.browsers.push(brave.clone());
+        assert!(check_browsers(&config).is_err(), "defined twice");
+        config.browsers = vec![BrowserDefinition {
+            app: Some(" ".to_string()),
+            ..brave.clone()
+        }];
+        assert!(check_browsers(&config).is_err(), "no application");
+        config.browsers = vec![
+            brave.clone(),
+            BrowserDefinition {
+                name: "safari".to_string(),
+                ..brave
+            },
+        ];
+        assert!(check_browsers(&config).is_err(), "shadows a built-in");
+    }
+
+    #[test]
+    fn test_resolve_bundle_ids() {
+        let app = |target: &str, bundle_id: Option<&str>| Action::App {
+            target: target.to_string(),
+            bundle_id: bundle_id.map(String::from),
+            toggle: false,
+        };
+        let mut config = Config::default();
+        config.bindings.push(Binding {
+            actions: vec![app("Safari", Some("com.apple.Safari"))],
+            modified: vec![ModifiedActions {
+                modifiers: vec![ExtraModifier::Shift],
+                actions: vec![app("Notes", None)],
+            }],
+            ..binding("s", None)
+        });
+        config.settings.anchor_tap_action = Some(app("Missing", None));
+
+        let app_actions = config
+            .bindings
+            .iter_mut()
+            .flat_map(|b| b.modified.iter_mut().flat_map(|m| &mut m.actions))
+            .chain(&mut config.settings.anchor_tap_action);
+        let mut report = ValidationReport::default();
+        let resolve = |name: &str| match name {
+            "Notes" => Ok("com.apple.Notes".to_string()),
+            _ => bail!("not installed"),
+        };
+        resolve_bundle_ids(app_actions, resolve, &mut report);
+
+        assert_eq!(report.resolved.len(), 1);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("'Missing'"));
+        let Action::App { bundle_id, .. } = &config.bindings[0].modified[0].actions[0] else {
+            panic!("not an app action");
+        };
+        assert_eq!(bundle_id.as_deref(), Some("com.apple.Notes"));
+    }
+
+    #[test]
+    fn test_missing_open_path_is_a_warning() {
+        let mut config = Config::default();
+        config.bindings.push(Binding {
+            actions: vec![Action::Open {
+                path: "/nonexistent/rcmdb-notes.md".to_string(),
+                with_app: None,
+            }],
+            ..binding("o", None)
+        });
+        let report = validate_and_update_config(&mut config).unwrap();
+        assert!(report.resolved.is_empty(), "not a bundle ID update");
+        assert_eq!(
+            report.warnings,
+            ["Warning: '/nonexistent/rcmdb-notes.md' does not exist (binding o)"]
+        );
+    }
 
     #[test]
     fn test_resolve_bundle_id() {